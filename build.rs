@@ -0,0 +1,12 @@
+use std::{env, fs, path::PathBuf};
+
+// puts `memory.x` where the linker finds it before the one of nrf52840-hal, which
+// doesn't keep the settings page free
+fn main() {
+    let out = PathBuf::from(env::var_os("OUT_DIR").unwrap());
+    fs::copy("memory.x", out.join("memory.x")).unwrap();
+    println!("cargo:rustc-link-search={}", out.display());
+
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=memory.x");
+}
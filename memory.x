@@ -0,0 +1,8 @@
+/* Linker script for the nRF52840 - WITHOUT SOFT DEVICE */
+MEMORY
+{
+  /* NOTE K = KiBi = 1024 bytes */
+  /* the last 4K page of the flash holds the settings, see `settings::storage` */
+  FLASH : ORIGIN = 0x00000000, LENGTH = 1020K
+  RAM : ORIGIN = 0x20000000, LENGTH = 256K
+}
@@ -0,0 +1,233 @@
+use super::profile::Thresholds;
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct EscalationPolicy {
+    // beeps sounded back to back each time the alarm goes off
+    pub initial_beeps: u16,
+    pub repeat_interval_ms: u64,
+    pub max_repetitions: u16,
+    // start over each time CO2 has stayed above the limit this much longer
+    pub realert_after_ms: Option<u64>,
+}
+
+impl EscalationPolicy {
+    pub fn new(
+        initial_beeps: u16,
+        repeat_interval_ms: u64,
+        max_repetitions: u16,
+        realert_after_ms: Option<u64>,
+    ) -> EscalationPolicy {
+        EscalationPolicy {
+            initial_beeps,
+            repeat_interval_ms,
+            max_repetitions,
+            realert_after_ms,
+        }
+    }
+}
+
+impl Default for EscalationPolicy {
+    fn default() -> Self {
+        EscalationPolicy::new(1, 30 * 1000, 5, Some(30 * 60 * 1000))
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum AlertLevel {
+    Good,
+    Elevated,
+    High,
+    Limit,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct AlertState {
+    pub level: AlertLevel,
+    pub above_limit_since: Option<u64>,
+    pub last_alarm: Option<u64>,
+    pub repetitions: u16,
+    // how often the alarm started over since CO2 went above the limit
+    pub realerts: u16,
+    pub silenced: bool,
+}
+
+impl AlertState {
+    pub fn new() -> AlertState {
+        AlertState {
+            level: AlertLevel::Good,
+            above_limit_since: None,
+            last_alarm: None,
+            repetitions: 0,
+            realerts: 0,
+            silenced: false,
+        }
+    }
+
+    // moves to the level of `co2`, true if the alarm has to go off now; during the quiet
+    // hours it stays silent and the repetitions are kept for later
+    pub fn update(
+        &mut self,
+        co2: f32,
+        thresholds: &Thresholds,
+        policy: &EscalationPolicy,
+        millis: u64,
+        quiet: bool,
+    ) -> bool {
+        if co2 > thresholds.limit_level {
+            self.level = AlertLevel::Limit;
+            self.above_limit_since.get_or_insert(millis);
+            self.silenced = quiet;
+
+            if quiet || !self.alarm_due(policy, millis) {
+                return false;
+            }
+
+            self.repetitions += 1;
+            self.last_alarm = Some(millis);
+            return true;
+        }
+
+        self.above_limit_since = None;
+        self.realerts = 0;
+
+        if co2 > thresholds.warning_level_2 {
+            self.level = AlertLevel::High;
+        } else if co2 > thresholds.warning_level_1 {
+            self.level = AlertLevel::Elevated;
+        } else {
+            self.reset();
+        }
+
+        false
+    }
+
+    fn alarm_due(&mut self, policy: &EscalationPolicy, millis: u64) -> bool {
+        let last_alarm = match self.last_alarm {
+            Some(last_alarm) => last_alarm,
+            None => return true,
+        };
+
+        if self.repetitions < policy.max_repetitions {
+            return millis.saturating_sub(last_alarm) >= policy.repeat_interval_ms;
+        }
+
+        let realert_after_ms = match policy.realert_after_ms {
+            Some(realert_after_ms) if realert_after_ms > 0 => realert_after_ms,
+            _ => return false,
+        };
+        let above_limit = millis.saturating_sub(self.above_limit_since.unwrap_or(millis));
+        let realerts = (above_limit / realert_after_ms).min(u16::MAX as u64) as u16;
+
+        if realerts > self.realerts {
+            self.realerts = realerts;
+            self.repetitions = 0;
+            return true;
+        }
+
+        false
+    }
+
+    pub fn reset(&mut self) {
+        *self = AlertState::new();
+    }
+}
+
+impl Default for AlertState {
+    fn default() -> Self {
+        AlertState::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINUTE_MS: u64 = 60 * 1000;
+
+    fn thresholds() -> Thresholds {
+        Thresholds::new(500.0, 700.0, 1000.0)
+    }
+
+    // 3 alarms a minute apart, then again after 10 minutes above the limit
+    fn policy() -> EscalationPolicy {
+        EscalationPolicy::new(2, MINUTE_MS, 3, Some(10 * MINUTE_MS))
+    }
+
+    // the times an alarm went off, checking every 30 s like the main loop
+    fn alarms(state: &mut AlertState, co2: f32, from: u64, until: u64, quiet: bool) -> Vec<u64> {
+        (from..until)
+            .step_by(30 * 1000)
+            .filter(|millis| state.update(co2, &thresholds(), &policy(), *millis, quiet))
+            .map(|millis| millis / MINUTE_MS)
+            .collect()
+    }
+
+    #[test]
+    fn levels_follow_the_thresholds() {
+        let mut state = AlertState::new();
+
+        assert!(!state.update(600.0, &thresholds(), &policy(), 0, false));
+        assert!(state.level == AlertLevel::Elevated);
+        assert!(!state.update(800.0, &thresholds(), &policy(), 0, false));
+        assert!(state.level == AlertLevel::High);
+        assert!(state.update(1200.0, &thresholds(), &policy(), 0, false));
+        assert!(state.level == AlertLevel::Limit);
+        assert!(state.above_limit_since == Some(0));
+
+        state.update(400.0, &thresholds(), &policy(), MINUTE_MS, false);
+        assert!(state == AlertState::new());
+    }
+
+    #[test]
+    fn escalation_stops_after_the_repetitions() {
+        let mut state = AlertState::new();
+
+        assert_eq!(
+            alarms(&mut state, 1200.0, 0, 10 * MINUTE_MS, false),
+            [0, 1, 2]
+        );
+        assert_eq!(state.repetitions, 3);
+    }
+
+    #[test]
+    fn realert_while_above_the_limit() {
+        let mut state = AlertState::new();
+
+        assert_eq!(
+            alarms(&mut state, 1200.0, 0, 25 * MINUTE_MS, false),
+            [0, 1, 2, 10, 11, 12, 20, 21, 22]
+        );
+        assert_eq!(state.realerts, 2);
+    }
+
+    #[test]
+    fn realert_counts_from_crossing_the_limit_again() {
+        let mut state = AlertState::new();
+
+        alarms(&mut state, 1200.0, 0, 8 * MINUTE_MS, false);
+        // still high but below the limit, the escalation carries on from there
+        alarms(&mut state, 800.0, 8 * MINUTE_MS, 9 * MINUTE_MS, false);
+        assert!(state.above_limit_since.is_none());
+
+        assert_eq!(
+            alarms(&mut state, 1200.0, 9 * MINUTE_MS, 20 * MINUTE_MS, false),
+            [19]
+        );
+    }
+
+    #[test]
+    fn quiet_hours_silence_the_alarm() {
+        let mut state = AlertState::new();
+
+        assert!(alarms(&mut state, 1200.0, 0, 5 * MINUTE_MS, true).is_empty());
+        assert!(state.silenced);
+        assert_eq!(state.repetitions, 0);
+
+        // the alarm goes off once the quiet hours are over
+        assert_eq!(
+            alarms(&mut state, 1200.0, 5 * MINUTE_MS, 8 * MINUTE_MS, false),
+            [5, 6, 7]
+        );
+        assert!(!state.silenced);
+    }
+}
@@ -1,44 +1,124 @@
-use nrf52840_hal::{pac::TIMER1, timer::OneShot, Timer};
+use crate::{
+    buzzer::{pattern::Pattern, Buzzer, ALARM, EXPOSURE_ALARM},
+    clock::DateTime,
+    exposure::ExposureTracker,
+    rgb_led::{Colour, Indicator},
+};
 
-use crate::{buzzer::Buzzer, rgb_led::LEDColour};
+pub mod escalation;
+pub mod profile;
+pub mod quiet_hours;
+
+use escalation::{AlertState, EscalationPolicy};
+use profile::{Profile, Thresholds};
+use quiet_hours::QuietHours;
 
 pub struct CO2alert {
-    warning_level_1: f32,
-    warning_level_2: f32,
-    limit_level: f32,
-    buzzer_count: u16,
+    profile: Profile,
+    quiet_hours: Option<QuietHours>,
+    escalation: EscalationPolicy,
+    state: AlertState,
+    exposure_alerted: bool,
 }
 
 impl CO2alert {
     pub fn init(warning_level_1: f32, warning_level_2: f32, limit_level: f32) -> CO2alert {
-        CO2alert {
+        CO2alert::with_profile(Profile::Custom(Thresholds::new(
             warning_level_1,
             warning_level_2,
             limit_level,
-            buzzer_count: 0,
+        )))
+    }
+
+    pub fn with_profile(profile: Profile) -> CO2alert {
+        CO2alert {
+            profile,
+            quiet_hours: None,
+            escalation: EscalationPolicy::default(),
+            state: AlertState::new(),
+            exposure_alerted: false,
+        }
+    }
+
+    pub fn profile(&self) -> Profile {
+        self.profile
+    }
+
+    pub fn thresholds(&self) -> Thresholds {
+        self.profile.thresholds()
+    }
+
+    pub fn set_profile(&mut self, profile: Profile) {
+        self.profile = profile;
+    }
+
+    pub fn quiet_hours(&self) -> Option<QuietHours> {
+        self.quiet_hours
+    }
+
+    pub fn set_quiet_hours(&mut self, quiet_hours: Option<QuietHours>) {
+        self.quiet_hours = quiet_hours;
+    }
+
+    pub fn escalation(&self) -> EscalationPolicy {
+        self.escalation
+    }
+
+    pub fn set_escalation(&mut self, escalation: EscalationPolicy) {
+        self.escalation = escalation;
+    }
+
+    pub fn state(&self) -> AlertState {
+        self.state
+    }
+
+    pub fn is_quiet(&self, now: &DateTime) -> bool {
+        match self.quiet_hours {
+            Some(quiet_hours) => quiet_hours.is_active(now),
+            None => false,
         }
     }
 
     pub fn check_level(
         &mut self,
         current_level: &f32,
+        millis: u64,
+        now: &DateTime,
+        buzzer: &mut Buzzer,
+        led: &mut impl Indicator,
+    ) {
+        let thresholds = self.thresholds();
+        let quiet = self.is_quiet(now);
+        let alarm = self
+            .state
+            .update(*current_level, &thresholds, &self.escalation, millis, quiet);
+
+        led.set_colour(Colour::from_co2(*current_level, &thresholds));
+
+        if alarm {
+            buzzer.play(
+                Pattern::Melody(ALARM, self.escalation.initial_beeps),
+                millis,
+            );
+        }
+    }
+
+    pub fn check_exposure(
+        &mut self,
+        exposure: &ExposureTracker,
+        millis: u64,
+        now: &DateTime,
         buzzer: &mut Buzzer,
-        led: &mut LEDColour,
-        mut timer: &mut Timer<TIMER1, OneShot>,
+        led: &mut impl Indicator,
     ) {
-        if *current_level > self.limit_level {
-            led.red();
-            if self.buzzer_count < 5 {
-                buzzer.buzz(&mut timer);
-                self.buzzer_count += 1;
+        if exposure.limit_exceeded() {
+            led.set_colour(Colour::RED);
+            if !self.exposure_alerted && !self.is_quiet(now) {
+                buzzer.play(Pattern::Melody(EXPOSURE_ALARM, 1), millis);
+                self.exposure_alerted = true;
             }
-        } else if *current_level > self.warning_level_2 {
-            led.yellow();
-        } else if *current_level > self.warning_level_1 {
-            led.blue();
         } else {
-            led.green();
-            self.buzzer_count = 0;
+            self.exposure_alerted = false;
         }
     }
 }
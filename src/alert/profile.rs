@@ -0,0 +1,117 @@
+#[derive(Clone, Copy, PartialEq)]
+pub struct Thresholds {
+    pub warning_level_1: f32,
+    pub warning_level_2: f32,
+    pub limit_level: f32,
+}
+
+impl Thresholds {
+    pub fn new(warning_level_1: f32, warning_level_2: f32, limit_level: f32) -> Thresholds {
+        Thresholds {
+            warning_level_1,
+            warning_level_2,
+            limit_level,
+        }
+    }
+
+    pub fn air_quality(&self, co2: f32) -> AirQuality {
+        if co2 > self.warning_level_2 {
+            AirQuality::Poor
+        } else if co2 > self.warning_level_1 {
+            AirQuality::Moderate
+        } else {
+            AirQuality::Good
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum AirQuality {
+    Good,
+    Moderate,
+    Poor,
+}
+
+impl AirQuality {
+    pub fn name(&self) -> &'static str {
+        match self {
+            AirQuality::Good => "Good",
+            AirQuality::Moderate => "Moderate",
+            AirQuality::Poor => "Poor",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Profile {
+    Standard,
+    // German Umweltbundesamt: above 1000 ppm noticeable, above 2000 ppm unacceptable
+    Uba,
+    // ASHRAE 62.1: roughly 700 ppm above outdoor air, i.e. about 1100 ppm indoors
+    Ashrae,
+    // UK BB101 school guidance: daily average below 1500 ppm, 1000 ppm achievable
+    Classroom,
+    // lower limits for undisturbed sleep
+    Bedroom,
+    Custom(Thresholds),
+}
+
+impl Profile {
+    pub fn thresholds(&self) -> Thresholds {
+        match self {
+            Profile::Standard => Thresholds::new(500.0, 700.0, 1000.0),
+            Profile::Uba => Thresholds::new(1000.0, 1500.0, 2000.0),
+            Profile::Ashrae => Thresholds::new(800.0, 1000.0, 1100.0),
+            Profile::Classroom => Thresholds::new(800.0, 1000.0, 1500.0),
+            Profile::Bedroom => Thresholds::new(600.0, 800.0, 1000.0),
+            Profile::Custom(thresholds) => *thresholds,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Profile::Standard => "Standard",
+            Profile::Uba => "UBA",
+            Profile::Ashrae => "ASHRAE",
+            Profile::Classroom => "Classroom",
+            Profile::Bedroom => "Bedroom",
+            Profile::Custom(_) => "Custom",
+        }
+    }
+
+    // stored in the settings, the thresholds of a custom profile are stored separately
+    pub fn index(&self) -> u16 {
+        match self {
+            Profile::Standard => 0,
+            Profile::Uba => 1,
+            Profile::Ashrae => 2,
+            Profile::Classroom => 3,
+            Profile::Bedroom => 4,
+            Profile::Custom(_) => 5,
+        }
+    }
+
+    // None for the index of a custom profile
+    pub fn preset(index: u16) -> Option<Profile> {
+        match index {
+            0 => Some(Profile::Standard),
+            1 => Some(Profile::Uba),
+            2 => Some(Profile::Ashrae),
+            3 => Some(Profile::Classroom),
+            4 => Some(Profile::Bedroom),
+            _ => None,
+        }
+    }
+
+    // cycles through the presets, a custom profile goes back to the first preset
+    pub fn next(&self) -> Profile {
+        match self {
+            Profile::Standard => Profile::Uba,
+            Profile::Uba => Profile::Ashrae,
+            Profile::Ashrae => Profile::Classroom,
+            Profile::Classroom => Profile::Bedroom,
+            Profile::Bedroom => Profile::Standard,
+            Profile::Custom(_) => Profile::Standard,
+        }
+    }
+}
@@ -0,0 +1,25 @@
+use crate::clock::{DateTime, TimeOfDay, Weekdays};
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct QuietHours {
+    pub start: TimeOfDay,
+    pub end: TimeOfDay,
+    // days on which the quiet period starts
+    pub days: Weekdays,
+}
+
+impl QuietHours {
+    pub fn new(start: TimeOfDay, end: TimeOfDay, days: Weekdays) -> QuietHours {
+        QuietHours { start, end, days }
+    }
+
+    pub fn is_active(&self, now: &DateTime) -> bool {
+        if self.start <= self.end {
+            self.days.contains(now.weekday) && now.time >= self.start && now.time < self.end
+        } else {
+            // the period runs past midnight, e.g. 22:00 - 07:00
+            (self.days.contains(now.weekday) && now.time >= self.start)
+                || (self.days.contains(now.weekday.previous()) && now.time < self.end)
+        }
+    }
+}
@@ -0,0 +1,186 @@
+use embedded_hal::{
+    adc::{Channel, OneShot},
+    blocking::i2c::{Write, WriteRead},
+};
+use nrf52840_hal::saadc::Saadc;
+
+use crate::rgb_led::LEDColour;
+
+pub trait AmbientLight {
+    type Error;
+
+    fn read_lux(&mut self) -> Result<f32, Self::Error>;
+}
+
+// 14 bit single ended SAADC reading at full scale
+const SAADC_FULL_SCALE: f32 = 16383.0;
+
+// a photoresistor in a voltage divider on an analog input, brighter light gives a higher
+// voltage; `full_scale_lux` is a rough linear calibration for a full scale reading
+pub struct Photoresistor<PIN> {
+    saadc: Saadc,
+    pin: PIN,
+    full_scale_lux: f32,
+}
+
+impl<PIN> Photoresistor<PIN>
+where
+    PIN: Channel<Saadc, ID = u8>,
+{
+    pub fn init(saadc: Saadc, pin: PIN, full_scale_lux: f32) -> Self {
+        Photoresistor {
+            saadc,
+            pin,
+            full_scale_lux,
+        }
+    }
+}
+
+impl<PIN> AmbientLight for Photoresistor<PIN>
+where
+    PIN: Channel<Saadc, ID = u8>,
+{
+    type Error = ();
+
+    fn read_lux(&mut self) -> Result<f32, ()> {
+        let raw = nb::block!(self.saadc.read(&mut self.pin))?;
+
+        Ok(raw.max(0) as f32 / SAADC_FULL_SCALE * self.full_scale_lux)
+    }
+}
+
+const VEML7700_ADDRESS: u8 = 0x10;
+
+enum Register {
+    Configuration = 0x00,
+    AmbientLight = 0x04,
+}
+
+// gain x1 and 100 ms integration time
+const VEML7700_CONFIGURATION: u16 = 0x0000;
+const VEML7700_LUX_PER_COUNT: f32 = 0.0576;
+
+pub struct VEML7700<T>(T);
+
+impl<T, E> VEML7700<T>
+where
+    T: Write<Error = E> + WriteRead<Error = E>,
+{
+    pub fn init(i2c: T) -> Result<Self, E> {
+        let mut sensor = VEML7700(i2c);
+        sensor.write_register(Register::Configuration, VEML7700_CONFIGURATION)?;
+
+        Ok(sensor)
+    }
+
+    fn write_register(&mut self, register: Register, value: u16) -> Result<(), E> {
+        let value = value.to_le_bytes();
+
+        self.0
+            .write(VEML7700_ADDRESS, &[register as u8, value[0], value[1]])
+    }
+
+    fn read_register(&mut self, register: Register) -> Result<u16, E> {
+        let mut rd_buffer = [0u8; 2];
+
+        self.0
+            .write_read(VEML7700_ADDRESS, &[register as u8], &mut rd_buffer)?;
+
+        Ok(u16::from_le_bytes(rd_buffer))
+    }
+}
+
+impl<T, E> AmbientLight for VEML7700<T>
+where
+    T: Write<Error = E> + WriteRead<Error = E>,
+{
+    type Error = E;
+
+    fn read_lux(&mut self) -> Result<f32, E> {
+        let counts = self.read_register(Register::AmbientLight)?;
+
+        Ok(counts as f32 * VEML7700_LUX_PER_COUNT)
+    }
+}
+
+pub struct AutoBrightness {
+    // below this the LED is switched off unless an alarm is active
+    pub darkness_lux: f32,
+    // at and above this the LED runs at full brightness
+    pub daylight_lux: f32,
+    pub min_brightness: u8,
+}
+
+impl AutoBrightness {
+    pub fn new(darkness_lux: f32, daylight_lux: f32, min_brightness: u8) -> AutoBrightness {
+        AutoBrightness {
+            darkness_lux,
+            daylight_lux,
+            min_brightness,
+        }
+    }
+
+    pub fn brightness(&self, lux: f32, alarm_active: bool) -> u8 {
+        if lux < self.darkness_lux {
+            return if alarm_active { self.min_brightness } else { 0 };
+        }
+
+        // the eye responds roughly logarithmically to light
+        let span = libm::logf(self.daylight_lux / self.darkness_lux.max(1.0));
+        let position = libm::logf(lux / self.darkness_lux.max(1.0)) / span;
+        let range = (255 - self.min_brightness) as f32;
+
+        self.min_brightness + (position.clamp(0.0, 1.0) * range) as u8
+    }
+
+    pub fn apply(&self, led: &mut LEDColour, lux: f32, alarm_active: bool) {
+        led.set_brightness(self.brightness(lux, alarm_active));
+    }
+}
+
+impl Default for AutoBrightness {
+    fn default() -> Self {
+        AutoBrightness::new(5.0, 300.0, 10)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dark_rooms_switch_the_led_off() {
+        let auto_brightness = AutoBrightness::default();
+
+        assert_eq!(auto_brightness.brightness(0.0, false), 0);
+        assert_eq!(auto_brightness.brightness(4.9, false), 0);
+        // an alarm stays visible
+        assert_eq!(auto_brightness.brightness(0.0, true), 10);
+    }
+
+    #[test]
+    fn brightness_follows_the_log_of_the_lux() {
+        let auto_brightness = AutoBrightness::default();
+
+        assert_eq!(auto_brightness.brightness(5.0, false), 10);
+        // half way between darkness and daylight on a log scale
+        let middle = auto_brightness.brightness(libm::sqrtf(5.0 * 300.0), false);
+        assert!((131..=133).contains(&middle), "{}", middle);
+        assert!(auto_brightness.brightness(299.0, false) >= 254);
+        assert_eq!(auto_brightness.brightness(100_000.0, false), 255);
+        assert_eq!(auto_brightness.brightness(100_000.0, true), 255);
+    }
+
+    #[test]
+    fn brighter_light_never_dims_the_led() {
+        let auto_brightness = AutoBrightness::new(2.0, 1000.0, 0);
+
+        let mut last = 0;
+        for lux in (2..2000).step_by(7) {
+            let brightness = auto_brightness.brightness(lux as f32, false);
+            assert!(brightness >= last);
+            last = brightness;
+        }
+        assert_eq!(last, 255);
+    }
+}
@@ -2,40 +2,76 @@
 #![no_std]
 
 use carbon_sensor::{
-    self as _, alert, buzzer,
-    display_helper::{clear_numbers, draw_numbers, draw_titles},
-    dk_button,
+    self as _,
+    alert::{self, escalation::AlertLevel, profile::Profile},
+    ambient_light::{AmbientLight, AutoBrightness, Photoresistor},
+    buzzer, clock,
+    display_helper::{
+        menu::draw_menu,
+        panel::{Epd4in2Panel, Panel},
+        refresh::{Refresh, RefreshPolicy},
+        screen::{Redraw, ScreenData, ScreenManager},
+    },
+    dk_button::{
+        self,
+        chord::{ChordDetector, ServiceAction},
+        ButtonEvent,
+    },
+    exposure, history,
     number_representations::Unit,
-    rgb_led, scd30,
+    rgb_led::{self, animation::Animation},
+    scd30,
+    settings::{
+        menu::{Menu, MenuInput, MenuResult},
+        storage::FlashStorage,
+        Settings,
+    },
+    statistics, ventilation,
 };
 
-use epd_waveshare::{epd4in2::*, prelude::*};
 // global logger + panicking-behavior + memory layout
-use nb::block;
+use embedded_graphics::DrawTarget;
 use nrf52840_hal::{
     self as hal,
+    clocks::LfOscConfiguration,
     gpio::{p0, p1, Level},
+    pac::interrupt,
     prelude::*,
+    saadc::{Saadc, SaadcConfig},
     spim::{self, Spim},
     twim::{self, Twim},
-    Temp, Timer,
+    Clocks, Temp, Timer,
 };
 
-const CO2_POSITION: (i32, i32) = (220, 90);
-const CO2_UNIT: &str = "ppm";
-const TEMP_POSITION: (i32, i32) = (220, 130);
-const TEMP_UNIT: &str = "°C";
-const HUMIDITY_POSITION: (i32, i32) = (220, 170);
-const HUMIDITY_UNIT: &str = "%";
+const HISTORY_SPAN_MS: u64 = 4 * 60 * 60 * 1000;
+// partial updates of the changed regions between two full refreshes of the panel
+const FULL_REFRESH_EVERY: u16 = 10;
+// queue button edges from the GPIOTE interrupt so presses aren't lost while the loop blocks
+const BUTTON_INTERRUPTS: bool = true;
+const MEASUREMENT_INTERVAL_MS: u64 = 5000;
+const DISPLAY_INTERVAL_MS: u64 = 30000;
+// while a button, a melody or an animation is going on the loop wakes up this often,
+// otherwise it sleeps until the next measurement or a button edge
+const TICK_MS: u64 = 5;
+// fresh outdoor air, used for the forced recalibration
+const FRC_REFERENCE_PPM: u16 = 420;
+// no reading for this long, or three measurement intervals if longer, counts as a sensor error
+const SENSOR_TIMEOUT_MS: u64 = 60 * 1000;
 
 #[cortex_m_rt::entry]
 fn main() -> ! {
     let board = hal::pac::Peripherals::take().unwrap();
-    let mut periodic_timer = Timer::periodic(board.TIMER0);
     let mut one_shot_timer = Timer::one_shot(board.TIMER1);
-    let mut delay = Timer::new(board.TIMER3);
+    let delay = Timer::new(board.TIMER3);
 
-    let mut millis: u64 = 0;
+    // the RTC runs from the low frequency clock, the 32.768 kHz crystal of the DK keeps it
+    // accurate where the RC oscillator drifts by a few percent
+    let _clocks = Clocks::new(board.CLOCK)
+        .set_lfclk_src_external(LfOscConfiguration::NoExternalNoBypass)
+        .start_lfclk();
+    clock::uptime::init(board.RTC1);
+    // there is no battery for the RTC, so the time is unknown until set in the menu
+    let mut wall_clock = clock::WallClock::init(board.RTC0);
 
     let pins_0 = p0::Parts::new(board.P0);
     let pins_1 = p1::Parts::new(board.P1);
@@ -53,7 +89,7 @@ fn main() -> ! {
         mosi: Some(din),
     };
 
-    let mut spi = Spim::new(
+    let spi = Spim::new(
         board.SPIM3,
         spi_pins,
         spim::Frequency::K500,
@@ -61,19 +97,63 @@ fn main() -> ! {
         0,
     );
 
-    let mut epd4in2 = EPD4in2::new(&mut spi, cs, busy, dc, rst, &mut delay).unwrap();
+    let mut panel = Epd4in2Panel::new(spi, cs, busy, dc, rst, delay).unwrap();
 
-    let display = Display4in2::default();
+    let mut display = panel.display();
+    let mut screens = ScreenManager::init();
+    let mut refresh_policy = RefreshPolicy::init(FULL_REFRESH_EVERY);
 
     let led_channel_red = pins_0.p0_03.degrade();
     let led_channel_blue = pins_0.p0_04.degrade();
     let led_channel_green = pins_0.p0_28.degrade();
 
-    let mut light = rgb_led::LEDColour::init(led_channel_red, led_channel_blue, led_channel_green);
+    let mut light = rgb_led::LEDColour::init(
+        board.PWM1,
+        led_channel_red,
+        led_channel_blue,
+        led_channel_green,
+    );
 
-    let mut buzzer = buzzer::Buzzer::init(pins_0.p0_29.degrade());
+    // the DK's own LEDs mirror the colour, for boards without the RGB LED wired up
+    let mut dk_leds = rgb_led::dk_leds::DkLeds::init(
+        pins_0.p0_13.degrade(),
+        pins_0.p0_14.degrade(),
+        pins_0.p0_15.degrade(),
+        pins_0.p0_16.degrade(),
+        rgb_led::Polarity::ActiveLow,
+    );
 
-    let mut co2_alert = alert::CO2alert::init(500_f32, 700_f32, 1000_f32);
+    // photoresistor divider on AIN0
+    let mut ambient_light = if cfg!(feature = "ambient-light") {
+        let saadc = Saadc::new(board.SAADC, SaadcConfig::default());
+        Some(Photoresistor::init(
+            saadc,
+            pins_0.p0_02.into_floating_input(),
+            1000_f32,
+        ))
+    } else {
+        None
+    };
+    let auto_brightness = AutoBrightness::default();
+
+    let mut buzzer = buzzer::Buzzer::init(board.PWM0, pins_0.p0_29.degrade());
+
+    let mut co2_alert = alert::CO2alert::with_profile(Profile::Standard);
+
+    // the profile is a preset, or custom once a threshold was changed in the menu
+    let mut storage = FlashStorage::init(board.NVMC);
+    let mut settings = storage.load().unwrap_or_default();
+    co2_alert.set_profile(settings.profile);
+    co2_alert.set_escalation(settings.escalation);
+    screens.set_layout(settings.layout);
+    let mut menu: Option<Menu> = None;
+    defmt::info!("Alert profile: {}", co2_alert.profile().name());
+    // made up quiet hours would silence the alarm at random, they wait for the clock
+    defmt::warn!("Clock not set, quiet hours are off until it is set in the menu");
+    let mut exposure = exposure::ExposureTracker::default();
+    let mut advisor = ventilation::VentilationAdvisor::init();
+    let mut history = history::History::init(HISTORY_SPAN_MS);
+    let mut statistics = statistics::ReadingStatistics::default();
 
     let scl = pins_0.p0_30.degrade();
     let sda = pins_0.p0_31.degrade();
@@ -100,6 +180,15 @@ fn main() -> ! {
     let mut button_3 = dk_button::Button::new(pins_0.p0_24.degrade());
     let mut button_4 = dk_button::Button::new(pins_0.p0_25.degrade());
 
+    let mut chords = ChordDetector::default();
+
+    if BUTTON_INTERRUPTS {
+        dk_button::gpiote::listen(
+            board.GPIOTE,
+            &mut [&mut button_1, &mut button_2, &mut button_3, &mut button_4],
+        );
+    }
+
     let mut temp = Temp::new(board.TEMP);
 
     let mut current_unit = Unit::Celsius;
@@ -113,106 +202,291 @@ fn main() -> ! {
     one_shot_timer.delay_ms(500_u32);
     light.green();
 
-    let mut display = draw_titles(display);
+    let mut reading: Option<scd30::SensorData> = None;
+    let mut last_reading_at = 0;
+    let mut sensor_error = false;
+    let mut measurement_interval = 0;
+    let mut redraw = false;
 
-    epd4in2.update_frame(&mut spi, &display.buffer()).unwrap();
-    epd4in2
-        .display_frame(&mut spi)
-        .expect("display frame new graphics");
+    let mut next_measurement = 0;
+    let mut next_display = 0;
 
     loop {
-        periodic_timer.start(1000u32);
+        let millis = clock::uptime::millis();
+        wall_clock.update();
+        buzzer.update(millis);
+        light.update(millis);
 
-        if (millis % 5000) == 0 {
+        if millis >= next_measurement {
+            next_measurement = millis + MEASUREMENT_INTERVAL_MS;
             defmt::info!("Tick (milliseconds): {=u64}", millis);
             temperature = temp.measure().to_num();
             let converted_temp = current_unit.convert_temperature(&temperature);
 
-            let unit = match current_unit {
-                Unit::Fahrenheit => "°F",
-                Unit::Kelvin => "K",
-                Unit::Celsius => "°C",
-            };
+            light.blink(millis);
 
-            light.blink(&mut one_shot_timer);
+            defmt::info!("{=f32} {}", converted_temp, current_unit.symbol());
 
-            defmt::info!("{=f32} {}", converted_temp, unit);
+            if let Some(sensor) = ambient_light.as_mut() {
+                let lux = sensor.read_lux().unwrap();
+                let alarm_active = co2_alert.state().level == AlertLevel::Limit;
+                auto_brightness.apply(&mut light, lux, alarm_active);
+                defmt::info!("Ambient light: {=f32} lux", lux);
+            }
 
             if sensor.data_ready().unwrap() {
                 defmt::info!("Sensor Data ready.");
-                one_shot_timer.delay_ms(50_u32);
-                light.blink(&mut one_shot_timer);
+                light.animate(Animation::blink(2), millis);
 
-                let measurement_interval = sensor.get_measurement_interval().unwrap();
+                measurement_interval = sensor.get_measurement_interval().unwrap();
 
                 defmt::info!("measurement_interval: {}", measurement_interval);
 
                 let result = sensor.read_measurement().unwrap();
+                exposure.update(&result, millis, wall_clock.now().weekday);
+                advisor.update(millis, result.co2);
+                history.update(millis, result.co2);
+                statistics.update(millis, wall_clock.now().weekday, &result);
 
                 let co2 = result.co2;
-                let temp = result.temperature;
+                let temp = current_unit.convert_temperature(&result.temperature);
                 let humidity = result.humidity;
 
                 defmt::info!(
                     "
                 CO2 {=f32} ppm
-                Temperature {=f32} °C
+                Temperature {=f32} {}
                 Humidity {=f32} %
                 ",
                     co2,
                     temp,
+                    current_unit.symbol(),
                     humidity
                 );
 
-                if (millis % 30000) == 0 {
-                    display = clear_numbers(
-                        display,
-                        CO2_POSITION,
-                        (CO2_POSITION.0 + 150, HUMIDITY_POSITION.1 + 20),
-                    );
+                let twa = exposure.time_weighted_average();
+                let stel = exposure.short_term_exposure();
+                defmt::info!("TWA {=f32} ppm, STEL {=f32} ppm", twa, stel);
+                defmt::info!(
+                    "Trend CO2 {}, temperature {}, humidity {}",
+                    statistics.co2.trend().name(),
+                    statistics.temperature.trend().name(),
+                    statistics.humidity.trend().name()
+                );
 
-                    display = draw_numbers(co2, CO2_UNIT, CO2_POSITION, display);
-                    display = draw_numbers(temp, TEMP_UNIT, TEMP_POSITION, display);
-                    display = draw_numbers(humidity, HUMIDITY_UNIT, HUMIDITY_POSITION, display);
+                if millis >= next_display {
+                    next_display = millis + DISPLAY_INTERVAL_MS;
+                    redraw = true;
+
+                    let now = wall_clock.now();
+                    defmt::info!(
+                        "{} {=u8}:{=u8}, quiet hours: {=bool}",
+                        now.weekday.name(),
+                        now.time.hour,
+                        now.time.minute,
+                        co2_alert.is_quiet(&now)
+                    );
 
-                    epd4in2.update_frame(&mut spi, &display.buffer()).unwrap();
-                    epd4in2
-                        .display_frame(&mut spi)
-                        .expect("display frame new graphics");
+                    co2_alert.check_level(&co2, millis, &now, &mut buzzer, &mut light);
 
-                    co2_alert.check_level(&co2, &mut buzzer, &mut light, &mut one_shot_timer);
+                    let alert_state = co2_alert.state();
+                    light.set_background_animation(
+                        Animation::for_alert_level(alert_state.level),
+                        millis,
+                    );
+                    defmt::info!(
+                        "Alarm repetitions: {=u16}, silenced: {=bool}",
+                        alert_state.repetitions,
+                        alert_state.silenced
+                    );
+                    co2_alert.check_exposure(&exposure, millis, &now, &mut buzzer, &mut light);
+                    dk_leds.set_colour(light.colour());
                 }
+
+                // show the first reading straight away
+                redraw |= reading.is_none() || sensor_error;
+                reading = Some(result);
+                last_reading_at = millis;
+                sensor_error = false;
             } else {
                 defmt::info!("Sensor Data Not Ready.");
+
+                let timeout = SENSOR_TIMEOUT_MS.max(3000 * measurement_interval as u64);
+                if reading.is_some() && !sensor_error && millis - last_reading_at > timeout {
+                    defmt::warn!("No sensor data for {=u64} ms", millis - last_reading_at);
+                    sensor_error = true;
+                    redraw = true;
+                }
             }
         };
 
-        if (millis % 5) == 0 {
-            if button_1.check_rising_edge() {
-                current_unit = match current_unit {
-                    Unit::Fahrenheit => Unit::Kelvin,
-                    Unit::Kelvin => Unit::Celsius,
-                    Unit::Celsius => Unit::Fahrenheit,
+        // the menu keeps the panel until it is closed
+        if redraw && menu.is_none() {
+            if let Some(reading) = reading.as_ref() {
+                let data = ScreenData {
+                    co2: reading.co2,
+                    temperature: reading.temperature,
+                    temperature_unit: current_unit,
+                    humidity: reading.humidity,
+                    twa: exposure.time_weighted_average(),
+                    stel: exposure.short_term_exposure(),
+                    advice: advisor.advice(&co2_alert.thresholds()),
+                    alert_level: co2_alert.state().level,
+                    silenced: co2_alert.state().silenced,
+                    sensor_error,
+                    history: &history,
+                    statistics: &statistics,
+                    thresholds: co2_alert.thresholds(),
+                    profile: co2_alert.profile().name(),
+                    firmware_version,
+                    measurement_interval,
+                    temperature_offset,
+                    uptime_ms: millis,
                 };
 
-                light.blink(&mut one_shot_timer);
-                defmt::info!("Unit changed");
+                let (rendered, changed) = screens.render(display, &data);
+                display = rendered;
+
+                let region = match changed {
+                    Redraw::Nothing => None,
+                    Redraw::Full => {
+                        refresh_policy.force_full();
+                        let size = display.size();
+                        Some(((0, 0), (size.width as i32 - 1, size.height as i32 - 1)))
+                    }
+                    Redraw::Region(top_left, bottom_right) => Some((top_left, bottom_right)),
+                };
+
+                if let Some((top_left, bottom_right)) = region {
+                    let refresh = refresh_policy
+                        .refresh(&mut panel, &display, top_left, bottom_right)
+                        .expect("display frame new graphics");
+                    defmt::info!("Full refresh: {=bool}", refresh == Refresh::Full);
+                }
             }
 
-            if button_2.check_rising_edge() {
-                sensor.stop_continuous_measurement().unwrap();
+            redraw = false;
+        }
+
+        let events = [
+            button_1.update(millis),
+            button_2.update(millis),
+            button_3.update(millis),
+            button_4.update(millis),
+        ];
+
+        let service_action = chords.update(
+            &mut [&mut button_1, &mut button_2, &mut button_3, &mut button_4],
+            millis,
+        );
+
+        if let Some(open_menu) = menu.as_mut() {
+            // up and down repeat while held, the buttons of a chord don't move through the menu
+            let input = match events {
+                _ if chords.is_engaged() => None,
+                [Some(ButtonEvent::Pressed), ..] | [Some(ButtonEvent::Repeat), ..] => {
+                    Some(MenuInput::Up)
+                }
+                [_, Some(ButtonEvent::Pressed), ..] | [_, Some(ButtonEvent::Repeat), ..] => {
+                    Some(MenuInput::Down)
+                }
+                [_, _, Some(ButtonEvent::Pressed), _] => Some(MenuInput::Select),
+                [_, _, _, Some(ButtonEvent::Pressed)] => Some(MenuInput::Back),
+                _ => None,
+            };
 
-                light.blink(&mut one_shot_timer);
-                defmt::info!("Stop continuous measurement");
+            if let Some(input) = input {
+                match open_menu.handle(input) {
+                    MenuResult::Open => {
+                        display = draw_menu(open_menu, current_unit, display);
+                    }
+                    MenuResult::Closed(changed, clock) => {
+                        if let Some(clock) = clock {
+                            wall_clock.set(clock.weekday, clock.time);
+                            defmt::info!(
+                                "Clock set to {} {=u8}:{=u8}",
+                                clock.weekday.name(),
+                                clock.time.hour,
+                                clock.time.minute
+                            );
+                        }
+
+                        if let Some(changed) = changed {
+                            settings = changed;
+                            storage.save(&settings);
+                            defmt::info!("Settings saved");
+
+                            co2_alert.set_profile(settings.profile);
+                            co2_alert.set_escalation(settings.escalation);
+                            screens.set_layout(settings.layout);
+                            sensor
+                                .set_measurement_interval(settings.measurement_interval)
+                                .unwrap();
+                            sensor
+                                .set_temperature_offset(settings.temperature_offset)
+                                .unwrap();
+                            sensor
+                                .start_continuous_measurement(&settings.pressure)
+                                .unwrap();
+                        }
+
+                        if wall_clock.is_set() {
+                            co2_alert.set_quiet_hours(settings.quiet_hours());
+                        }
+
+                        menu = None;
+                        screens.invalidate();
+                        redraw = true;
+                    }
+                }
+
+                if menu.is_some() {
+                    refresh_policy
+                        .refresh(&mut panel, &display, (0, 0), (i32::MAX, i32::MAX))
+                        .expect("display frame new graphics");
+                }
+            }
+        } else {
+            match events[0] {
+                Some(ButtonEvent::ShortClick) => {
+                    if buzzer.is_playing() {
+                        buzzer.cancel();
+                        defmt::info!("Alarm cancelled");
+                    } else {
+                        current_unit = current_unit.next();
+                        redraw = true;
+
+                        light.blink(millis);
+                        defmt::info!("Unit changed: {}", current_unit.symbol());
+                    }
+                }
+                Some(ButtonEvent::LongPress) => {
+                    // kept over a power cycle, like the settings from the menu
+                    settings.profile = co2_alert.profile().next();
+                    co2_alert.set_profile(settings.profile);
+                    storage.save(&settings);
+
+                    light.blink(millis);
+                    defmt::info!("Alert profile: {}", settings.profile.name());
+                }
+                _ => {}
             }
 
-            if button_3.check_rising_edge() {
-                sensor.set_measurement_interval(2_u16).unwrap();
-                sensor.set_temperature_offset(0_u16).unwrap();
+            if let Some(ButtonEvent::ShortClick) = events[1] {
+                screens.next_page();
+                redraw = true;
+                defmt::info!("Page: {}", screens.page().title());
+            }
 
-                let air_pressure_london = 1012_u16;
+            if let Some(ButtonEvent::ShortClick) = events[2] {
+                sensor
+                    .set_measurement_interval(settings.measurement_interval)
+                    .unwrap();
                 sensor
-                    .start_continuous_measurement(&air_pressure_london)
+                    .set_temperature_offset(settings.temperature_offset)
+                    .unwrap();
+                sensor
+                    .start_continuous_measurement(&settings.pressure)
                     .unwrap();
 
                 defmt::info!(
@@ -225,21 +499,99 @@ fn main() -> ! {
                     sensor.get_measurement_interval().unwrap()
                 );
 
-                light.blink(&mut one_shot_timer);
+                light.blink(millis);
             }
+        }
 
-            if button_4.check_rising_edge() {
-                sensor.soft_reset().unwrap();
-                defmt::info!("Sensor reset");
-                one_shot_timer.delay_ms(50_u32);
-                let auto_status = sensor.activate_auto_self_calibration().unwrap();
-                defmt::info!("Auto Calib Status, {}", auto_status);
+        if let Some(action) = service_action {
+            defmt::info!("Service action: {}", action.name());
 
-                light.blink(&mut one_shot_timer);
+            match action {
+                ServiceAction::StopMeasurement => {
+                    sensor.stop_continuous_measurement().unwrap();
+                }
+                ServiceAction::SoftReset => {
+                    sensor.soft_reset().unwrap();
+                    one_shot_timer.delay_ms(50_u32);
+                    let auto_status = sensor.activate_auto_self_calibration().unwrap();
+                    defmt::info!("Auto Calib Status, {}", auto_status);
+                }
+                ServiceAction::ForcedRecalibration => {
+                    sensor.set_forced_recalibration(FRC_REFERENCE_PPM).unwrap();
+                }
+                ServiceAction::FactoryReset => {
+                    settings = Settings::default();
+                    storage.save(&settings);
+
+                    sensor.soft_reset().unwrap();
+                    one_shot_timer.delay_ms(50_u32);
+                    sensor
+                        .set_measurement_interval(settings.measurement_interval)
+                        .unwrap();
+                    sensor
+                        .set_temperature_offset(settings.temperature_offset)
+                        .unwrap();
+                    sensor.activate_auto_self_calibration().unwrap();
+                    sensor
+                        .start_continuous_measurement(&settings.pressure)
+                        .unwrap();
+
+                    co2_alert.set_profile(settings.profile);
+                    co2_alert.set_escalation(settings.escalation);
+                    screens.set_layout(settings.layout);
+                    if wall_clock.is_set() {
+                        co2_alert.set_quiet_hours(settings.quiet_hours());
+                    }
+                    current_unit = Unit::Celsius;
+                    redraw = true;
+
+                    // an open menu still holds the old settings and would save them again
+                    if menu.take().is_some() {
+                        screens.invalidate();
+                        redraw = true;
+                    }
+                }
+                ServiceAction::SettingsMenu => {
+                    if menu.is_none() {
+                        let open_menu = Menu::open(settings, wall_clock.now());
+                        display = draw_menu(&open_menu, current_unit, display);
+                        refresh_policy
+                            .refresh(&mut panel, &display, (0, 0), (i32::MAX, i32::MAX))
+                            .expect("display frame new graphics");
+                        menu = Some(open_menu);
+                    }
+                }
             }
+
+            light.animate(Animation::blink(3), millis);
         }
 
-        block!(periodic_timer.wait()).unwrap();
-        millis = millis.saturating_add(1);
+        let buttons = [&button_1, &button_2, &button_3, &button_4];
+        let busy = !BUTTON_INTERRUPTS
+            || buttons.iter().any(|button| !button.is_idle())
+            || buzzer.is_playing()
+            || light.is_animating();
+        let wake_at = if busy {
+            millis + TICK_MS
+        } else {
+            next_measurement
+        };
+
+        // an edge queued after the buttons were updated keeps the loop awake
+        cortex_m::interrupt::free(|_| {
+            if !dk_button::gpiote::has_edges() {
+                clock::uptime::sleep_until(wake_at);
+            }
+        });
     }
 }
+
+#[interrupt]
+fn GPIOTE() {
+    dk_button::gpiote::on_interrupt();
+}
+
+#[interrupt]
+fn RTC1() {
+    clock::uptime::on_interrupt();
+}
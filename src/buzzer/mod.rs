@@ -1,37 +1,158 @@
 use nrf52840_hal::{
-    gpio::{Level, Output, Pin, PushPull},
-    pac::TIMER1,
-    prelude::*,
-    timer::OneShot,
-    Timer,
+    gpio::{Level, Output, Pin, Port, PushPull},
+    pac::PWM0,
 };
 
+pub mod pattern;
+
+use pattern::{Pattern, PatternPlayer};
+
+// PWM_CLK is 16 MHz divided by 2^prescaler
+const PWM_BASE_CLOCK: u32 = 16_000_000;
+const MAX_PRESCALER: u8 = 7;
+const MAX_COUNTERTOP: u32 = 32767;
+const MIN_COUNTERTOP: u32 = 3;
+
+const DEFAULT_VOLUME: u8 = 100;
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct Note {
+    // a frequency of 0 is a rest
+    pub frequency: u32,
+    pub duration_ms: u32,
+}
+
+impl Note {
+    pub const fn new(frequency: u32, duration_ms: u32) -> Note {
+        Note {
+            frequency,
+            duration_ms,
+        }
+    }
+
+    pub const fn rest(duration_ms: u32) -> Note {
+        Note::new(0, duration_ms)
+    }
+}
+
+pub const ALARM: &[Note] = &[
+    Note::new(2_000, 150),
+    Note::rest(50),
+    Note::new(2_600, 150),
+    Note::rest(50),
+    Note::new(2_000, 150),
+    Note::rest(50),
+    Note::new(2_600, 150),
+    Note::rest(200),
+];
+
+pub const EXPOSURE_ALARM: &[Note] = &[
+    Note::new(1_000, 400),
+    Note::rest(100),
+    Note::new(800, 600),
+    Note::rest(300),
+];
+
 pub struct Buzzer {
-    pin: Pin<Output<PushPull>>,
+    pwm: PWM0,
+    _pin: Pin<Output<PushPull>>,
+    // EasyDMA reads the duty cycle from RAM, so it needs a fixed address
+    duty: &'static mut [u16; 1],
+    volume: u8,
+    player: PatternPlayer,
 }
 
 impl Buzzer {
-    pub fn init<Mode>(pin: Pin<Mode>) -> Buzzer {
+    pub fn init<Mode>(pwm: PWM0, pin: Pin<Mode>) -> Buzzer {
+        let pin = pin.into_push_pull_output(Level::Low);
+
+        pwm.psel.out[0].write(|w| {
+            unsafe { w.pin().bits(pin.pin()) };
+            w.port().bit(matches!(pin.port(), Port::Port1));
+            w.connect().connected()
+        });
+        pwm.enable.write(|w| w.enable().enabled());
+        pwm.mode.write(|w| w.updown().up());
+        pwm.decoder
+            .write(|w| w.load().common().mode().refresh_count());
+        pwm.loop_.write(|w| w.cnt().disabled());
+
+        let duty = cortex_m::singleton!(: [u16; 1] = [0; 1]).unwrap();
+
+        pwm.seq0
+            .ptr
+            .write(|w| unsafe { w.bits(duty.as_ptr() as u32) });
+        pwm.seq0.cnt.write(|w| unsafe { w.bits(1) });
+        pwm.seq0.refresh.write(|w| unsafe { w.bits(0) });
+        pwm.seq0.enddelay.write(|w| unsafe { w.bits(0) });
+
         Buzzer {
-            pin: pin.into_push_pull_output(Level::Low),
+            pwm,
+            _pin: pin,
+            duty,
+            volume: DEFAULT_VOLUME,
+            player: PatternPlayer::new(),
         }
     }
 
-    pub fn high(&mut self) {
-        self.pin.set_high().unwrap();
+    // volume in percent, 100 gives a 50% duty cycle which is the loudest square wave
+    pub fn set_volume(&mut self, volume: u8) {
+        self.volume = volume.min(100);
     }
 
-    pub fn low(&mut self) {
-        self.pin.set_low().unwrap();
+    pub fn volume(&self) -> u8 {
+        self.volume
     }
 
-    pub fn buzz(&mut self, timer: &mut Timer<TIMER1, OneShot>) {
-        for _ in 1..250 {
-            self.low();
-            timer.delay_ms(3_u32);
+    pub fn tone(&mut self, frequency: u32) {
+        if frequency == 0 || self.volume == 0 {
+            self.silence();
+            return;
+        }
+
+        let mut prescaler = 0;
+        let mut countertop = PWM_BASE_CLOCK / frequency;
+        while countertop > MAX_COUNTERTOP && prescaler < MAX_PRESCALER {
+            prescaler += 1;
+            countertop = (PWM_BASE_CLOCK >> prescaler) / frequency;
+        }
+        let countertop = countertop.clamp(MIN_COUNTERTOP, MAX_COUNTERTOP);
+
+        self.duty[0] = (countertop * self.volume as u32 / 200) as u16;
+
+        self.pwm.prescaler.write(|w| w.prescaler().bits(prescaler));
+        self.pwm
+            .countertop
+            .write(|w| unsafe { w.countertop().bits(countertop as u16) });
+        self.pwm.tasks_seqstart[0].write(|w| unsafe { w.bits(1) });
+    }
+
+    fn silence(&mut self) {
+        self.pwm.tasks_stop.write(|w| unsafe { w.bits(1) });
+    }
+
+    pub fn play(&mut self, pattern: Pattern, millis: u64) {
+        let note = self.player.start(pattern, millis);
+        self.tone(note.frequency);
+    }
+
+    pub fn cancel(&mut self) {
+        self.player.cancel();
+        self.silence();
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.player.is_playing()
+    }
+
+    pub fn buzz(&mut self, millis: u64) {
+        self.play(Pattern::Melody(ALARM, 1), millis);
+    }
 
-            self.high();
-            timer.delay_ms(3_u32);
+    // advances the current pattern, call this from the main loop tick or a timer interrupt
+    pub fn update(&mut self, millis: u64) {
+        if let Some(note) = self.player.update(millis) {
+            self.tone(note.frequency);
         }
     }
 }
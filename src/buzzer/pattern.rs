@@ -0,0 +1,343 @@
+use super::Note;
+
+const MORSE_FREQUENCY: u32 = 1_000;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Pattern {
+    Beeps {
+        count: u16,
+        frequency: u32,
+        on_ms: u32,
+        off_ms: u32,
+    },
+    // repeats until cancelled, e.g. a long beep every 10 s
+    Periodic {
+        frequency: u32,
+        on_ms: u32,
+        period_ms: u32,
+    },
+    // dot length in ms
+    Morse(&'static str, u32),
+    Melody(&'static [Note], u16),
+}
+
+impl Pattern {
+    pub fn short_beeps(count: u16) -> Pattern {
+        Pattern::Beeps {
+            count,
+            frequency: 2_000,
+            on_ms: 100,
+            off_ms: 100,
+        }
+    }
+
+    pub fn long_beep_every(period_ms: u32) -> Pattern {
+        Pattern::Periodic {
+            frequency: 2_000,
+            on_ms: 1_000,
+            period_ms,
+        }
+    }
+
+    fn step(&self, index: u32) -> Option<Note> {
+        match *self {
+            Pattern::Beeps {
+                count,
+                frequency,
+                on_ms,
+                off_ms,
+            } => {
+                if index >= 2 * count as u32 {
+                    return None;
+                }
+
+                match index % 2 {
+                    0 => Some(Note::new(frequency, on_ms)),
+                    _ => Some(Note::rest(off_ms)),
+                }
+            }
+            Pattern::Periodic {
+                frequency,
+                on_ms,
+                period_ms,
+            } => match index % 2 {
+                0 => Some(Note::new(frequency, on_ms)),
+                _ => Some(Note::rest(period_ms.saturating_sub(on_ms))),
+            },
+            Pattern::Morse(text, dot_ms) => morse_step(text, dot_ms, index),
+            Pattern::Melody(notes, repeats) => {
+                if notes.is_empty() || index >= notes.len() as u32 * repeats as u32 {
+                    None
+                } else {
+                    Some(notes[index as usize % notes.len()])
+                }
+            }
+        }
+    }
+}
+
+pub struct PatternPlayer {
+    pattern: Option<Pattern>,
+    index: u32,
+    step: Note,
+    step_started: u64,
+}
+
+impl PatternPlayer {
+    pub fn new() -> PatternPlayer {
+        PatternPlayer {
+            pattern: None,
+            index: 0,
+            step: Note::rest(0),
+            step_started: 0,
+        }
+    }
+
+    // returns the first note to sound
+    pub fn start(&mut self, pattern: Pattern, millis: u64) -> Note {
+        self.index = 0;
+        self.step_started = millis;
+
+        match pattern.step(0) {
+            Some(step) => {
+                self.pattern = Some(pattern);
+                self.step = step;
+            }
+            None => {
+                self.pattern = None;
+                self.step = Note::rest(0);
+            }
+        }
+
+        self.step
+    }
+
+    pub fn cancel(&mut self) {
+        self.pattern = None;
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.pattern.is_some()
+    }
+
+    // returns the note to sound whenever the step changes, a rest once the pattern is over
+    pub fn update(&mut self, millis: u64) -> Option<Note> {
+        let pattern = self.pattern?;
+
+        if millis.saturating_sub(self.step_started) < self.step.duration_ms as u64 {
+            return None;
+        }
+
+        self.index += 1;
+        self.step_started = millis;
+        self.step = match pattern.step(self.index) {
+            Some(step) => step,
+            None => {
+                self.pattern = None;
+                Note::rest(0)
+            }
+        };
+
+        Some(self.step)
+    }
+}
+
+impl Default for PatternPlayer {
+    fn default() -> Self {
+        PatternPlayer::new()
+    }
+}
+
+fn morse_code(character: char) -> &'static str {
+    match character.to_ascii_uppercase() {
+        'A' => ".-",
+        'B' => "-...",
+        'C' => "-.-.",
+        'D' => "-..",
+        'E' => ".",
+        'F' => "..-.",
+        'G' => "--.",
+        'H' => "....",
+        'I' => "..",
+        'J' => ".---",
+        'K' => "-.-",
+        'L' => ".-..",
+        'M' => "--",
+        'N' => "-.",
+        'O' => "---",
+        'P' => ".--.",
+        'Q' => "--.-",
+        'R' => ".-.",
+        'S' => "...",
+        'T' => "-",
+        'U' => "..-",
+        'V' => "...-",
+        'W' => ".--",
+        'X' => "-..-",
+        'Y' => "-.--",
+        'Z' => "--..",
+        '0' => "-----",
+        '1' => ".----",
+        '2' => "..---",
+        '3' => "...--",
+        '4' => "....-",
+        '5' => ".....",
+        '6' => "-....",
+        '7' => "--...",
+        '8' => "---..",
+        '9' => "----.",
+        _ => "",
+    }
+}
+
+// every symbol is a tone followed by a gap: 1 dot within a letter,
+// 3 dots after the last symbol of a letter and another 4 for a word break
+fn morse_step(text: &str, dot_ms: u32, index: u32) -> Option<Note> {
+    let mut current = 0;
+
+    for character in text.chars() {
+        if character == ' ' {
+            if current == index {
+                return Some(Note::rest(4 * dot_ms));
+            }
+            current += 1;
+            continue;
+        }
+
+        let code = morse_code(character);
+        for (position, symbol) in code.chars().enumerate() {
+            if current == index {
+                let length = if symbol == '-' { 3 } else { 1 };
+                return Some(Note::new(MORSE_FREQUENCY, length * dot_ms));
+            }
+
+            if current + 1 == index {
+                let gap = if position + 1 == code.len() { 3 } else { 1 };
+                return Some(Note::rest(gap * dot_ms));
+            }
+            current += 2;
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // (frequency, duration_ms) of each step until the pattern is over
+    fn steps(pattern: Pattern) -> Vec<(u32, u32)> {
+        (0..)
+            .map(|index| pattern.step(index))
+            .take_while(Option::is_some)
+            .map(|step| {
+                let step = step.unwrap();
+                (step.frequency, step.duration_ms)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn beeps_alternate_with_rests() {
+        assert_eq!(
+            steps(Pattern::short_beeps(2)),
+            [(2_000, 100), (0, 100), (2_000, 100), (0, 100)]
+        );
+        assert!(steps(Pattern::short_beeps(0)).is_empty());
+    }
+
+    #[test]
+    fn melody_repeats() {
+        const NOTES: &[Note] = &[Note::new(440, 100), Note::rest(50)];
+
+        assert_eq!(
+            steps(Pattern::Melody(NOTES, 2)),
+            [(440, 100), (0, 50), (440, 100), (0, 50)]
+        );
+        assert!(steps(Pattern::Melody(&[], 3)).is_empty());
+    }
+
+    #[test]
+    fn periodic_pattern_never_ends() {
+        let pattern = Pattern::long_beep_every(10_000);
+
+        let step = pattern.step(1001).unwrap();
+        assert_eq!((step.frequency, step.duration_ms), (0, 9_000));
+    }
+
+    #[test]
+    fn morse_timing() {
+        assert_eq!(morse_code('s'), "...");
+        assert_eq!(morse_code('?'), "");
+
+        // "ET E": a dot, a dash, a word break and a dot
+        assert_eq!(
+            steps(Pattern::Morse("ET E", 100)),
+            [
+                (MORSE_FREQUENCY, 100),
+                (0, 300),
+                (MORSE_FREQUENCY, 300),
+                (0, 300),
+                (0, 400),
+                (MORSE_FREQUENCY, 100),
+                (0, 300),
+            ]
+        );
+
+        // the symbols of one letter are a dot apart
+        assert_eq!(
+            steps(Pattern::Morse("A", 50)),
+            [
+                (MORSE_FREQUENCY, 50),
+                (0, 50),
+                (MORSE_FREQUENCY, 150),
+                (0, 150)
+            ]
+        );
+    }
+
+    #[test]
+    fn player_steps_when_the_note_is_over() {
+        let mut player = PatternPlayer::new();
+
+        let first = player.start(Pattern::short_beeps(1), 1_000);
+        assert_eq!((first.frequency, first.duration_ms), (2_000, 100));
+        assert!(player.is_playing());
+
+        assert!(player.update(1_099).is_none());
+        let rest = player.update(1_100).unwrap();
+        assert_eq!((rest.frequency, rest.duration_ms), (0, 100));
+
+        // a late update still moves on by one step only
+        let end = player.update(5_000).unwrap();
+        assert_eq!(end.frequency, 0);
+        assert!(!player.is_playing());
+        assert!(player.update(6_000).is_none());
+    }
+
+    #[test]
+    fn empty_pattern_does_not_play() {
+        let mut player = PatternPlayer::new();
+
+        let first = player.start(Pattern::short_beeps(0), 0);
+        assert_eq!(first.frequency, 0);
+        assert!(!player.is_playing());
+    }
+
+    #[test]
+    fn cancel_stops_the_pattern() {
+        let mut player = PatternPlayer::new();
+
+        player.start(Pattern::long_beep_every(10_000), 0);
+        assert!(player.update(1_000).is_some());
+
+        player.cancel();
+        assert!(!player.is_playing());
+        assert!(player.update(20_000).is_none());
+
+        // and it can be started again
+        player.start(Pattern::short_beeps(1), 30_000);
+        assert!(player.is_playing());
+    }
+}
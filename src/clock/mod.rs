@@ -0,0 +1,179 @@
+use nrf52840_hal::{
+    pac::RTC0,
+    rtc::{Rtc, Started},
+};
+
+pub mod uptime;
+
+// fRTC = 32_768 / (4095 + 1) = 8 Hz
+const PRESCALER: u32 = 4095;
+const TICKS_PER_SECOND: u32 = 8;
+const COUNTER_MASK: u32 = 0x00FF_FFFF;
+
+const SECONDS_PER_MINUTE: u32 = 60;
+const SECONDS_PER_DAY: u32 = 24 * 60 * SECONDS_PER_MINUTE;
+const SECONDS_PER_WEEK: u32 = 7 * SECONDS_PER_DAY;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Weekday {
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Sunday,
+}
+
+impl Weekday {
+    pub fn from_index(index: u32) -> Weekday {
+        match index % 7 {
+            0 => Weekday::Monday,
+            1 => Weekday::Tuesday,
+            2 => Weekday::Wednesday,
+            3 => Weekday::Thursday,
+            4 => Weekday::Friday,
+            5 => Weekday::Saturday,
+            _ => Weekday::Sunday,
+        }
+    }
+
+    pub fn index(&self) -> u32 {
+        *self as u32
+    }
+
+    pub fn previous(&self) -> Weekday {
+        Weekday::from_index(self.index() + 6)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Weekday::Monday => "Mon",
+            Weekday::Tuesday => "Tue",
+            Weekday::Wednesday => "Wed",
+            Weekday::Thursday => "Thu",
+            Weekday::Friday => "Fri",
+            Weekday::Saturday => "Sat",
+            Weekday::Sunday => "Sun",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct Weekdays(u8);
+
+impl Weekdays {
+    pub const NONE: Weekdays = Weekdays(0);
+    pub const WORKDAYS: Weekdays = Weekdays(0b0001_1111);
+    pub const WEEKEND: Weekdays = Weekdays(0b0110_0000);
+    pub const EVERY_DAY: Weekdays = Weekdays(0b0111_1111);
+
+    pub fn with(self, day: Weekday) -> Weekdays {
+        Weekdays(self.0 | 1 << day.index())
+    }
+
+    pub fn contains(&self, day: Weekday) -> bool {
+        self.0 & 1 << day.index() != 0
+    }
+
+    // bit 0 is Monday, e.g. to store them in the settings
+    pub fn bits(&self) -> u8 {
+        self.0
+    }
+
+    pub fn from_bits(bits: u8) -> Weekdays {
+        Weekdays(bits & Weekdays::EVERY_DAY.0)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, PartialOrd)]
+pub struct TimeOfDay {
+    pub hour: u8,
+    pub minute: u8,
+}
+
+impl TimeOfDay {
+    pub fn new(hour: u8, minute: u8) -> TimeOfDay {
+        TimeOfDay {
+            hour: hour % 24,
+            minute: minute % 60,
+        }
+    }
+
+    // e.g. to store it in the settings
+    pub fn minutes(&self) -> u16 {
+        self.hour as u16 * 60 + self.minute as u16
+    }
+
+    pub fn from_minutes(minutes: u16) -> TimeOfDay {
+        TimeOfDay::new((minutes / 60 % 24) as u8, (minutes % 60) as u8)
+    }
+
+    fn seconds(&self) -> u32 {
+        (self.hour as u32 * 60 + self.minute as u32) * SECONDS_PER_MINUTE
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct DateTime {
+    pub weekday: Weekday,
+    pub time: TimeOfDay,
+    pub second: u8,
+}
+
+pub struct WallClock {
+    rtc: Rtc<RTC0, Started>,
+    last_counter: u32,
+    // RTC ticks since Monday 00:00, wraps every week
+    week_ticks: u32,
+    // the time is made up until it was set once
+    is_set: bool,
+}
+
+impl WallClock {
+    pub fn init(rtc0: RTC0) -> WallClock {
+        let mut rtc = Rtc::new(rtc0);
+        rtc.set_prescaler(PRESCALER).unwrap();
+        let rtc = rtc.enable_counter();
+
+        WallClock {
+            last_counter: rtc.get_counter(),
+            rtc,
+            week_ticks: 0,
+            is_set: false,
+        }
+    }
+
+    pub fn set(&mut self, weekday: Weekday, time: TimeOfDay) {
+        self.last_counter = self.rtc.get_counter();
+        self.week_ticks = (weekday.index() * SECONDS_PER_DAY + time.seconds()) * TICKS_PER_SECOND;
+        self.is_set = true;
+    }
+
+    pub fn is_set(&self) -> bool {
+        self.is_set
+    }
+
+    // must be called at least once per counter overflow (~24 days at 8 Hz)
+    pub fn update(&mut self) {
+        let counter = self.rtc.get_counter();
+        let elapsed = counter.wrapping_sub(self.last_counter) & COUNTER_MASK;
+
+        self.last_counter = counter;
+        self.week_ticks = (self.week_ticks + elapsed) % (SECONDS_PER_WEEK * TICKS_PER_SECOND);
+    }
+
+    pub fn now(&self) -> DateTime {
+        let seconds = self.week_ticks / TICKS_PER_SECOND;
+        let seconds_of_day = seconds % SECONDS_PER_DAY;
+
+        DateTime {
+            weekday: Weekday::from_index(seconds / SECONDS_PER_DAY),
+            time: TimeOfDay::new(
+                (seconds_of_day / 3600) as u8,
+                (seconds_of_day / 60 % 60) as u8,
+            ),
+            second: (seconds_of_day % 60) as u8,
+        }
+    }
+}
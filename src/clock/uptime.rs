@@ -0,0 +1,96 @@
+use core::cell::RefCell;
+
+use cortex_m::interrupt::{self, Mutex};
+use nrf52840_hal::{
+    pac::{self, RTC1},
+    rtc::{Rtc, RtcCompareReg, RtcInterrupt, Started},
+};
+
+// the undivided 32.768 kHz RTC, its counter wraps every 512 s
+const TICKS_PER_SECOND: u64 = 32_768;
+const COUNTER_MASK: u32 = 0x00FF_FFFF;
+// a compare value less than 2 ticks ahead of the counter may not raise the event
+const MIN_SLEEP_TICKS: u64 = 3;
+// half a counter period, so the counter can't pass the compare value unnoticed
+const MAX_SLEEP_TICKS: u64 = 1 << 23;
+
+struct Uptime {
+    rtc: Rtc<RTC1, Started>,
+    last_counter: u32,
+    ticks: u64,
+}
+
+impl Uptime {
+    fn ticks(&mut self) -> u64 {
+        let counter = self.rtc.get_counter();
+        self.ticks += (counter.wrapping_sub(self.last_counter) & COUNTER_MASK) as u64;
+        self.last_counter = counter;
+
+        self.ticks
+    }
+}
+
+static UPTIME: Mutex<RefCell<Option<Uptime>>> = Mutex::new(RefCell::new(None));
+
+// milliseconds since start up from RTC1, they keep counting while the CPU sleeps; the RTC
+// interrupt wakes the main loop from `sleep_until`
+pub fn init(rtc1: RTC1) {
+    let mut rtc = Rtc::new(rtc1);
+    rtc.set_prescaler(0).unwrap();
+    rtc.enable_interrupt(RtcInterrupt::Compare0, None);
+    let rtc = rtc.enable_counter();
+
+    interrupt::free(|cs| {
+        UPTIME.borrow(cs).replace(Some(Uptime {
+            last_counter: rtc.get_counter(),
+            rtc,
+            ticks: 0,
+        }));
+    });
+
+    unsafe { pac::NVIC::unmask(pac::Interrupt::RTC1) };
+}
+
+// must be called at least once per counter overflow, `sleep_until` wakes up before that
+pub fn millis() -> u64 {
+    interrupt::free(|cs| {
+        UPTIME
+            .borrow(cs)
+            .borrow_mut()
+            .as_mut()
+            .map_or(0, |uptime| uptime.ticks() * 1000 / TICKS_PER_SECOND)
+    })
+}
+
+// sleeps until `millis` or any other interrupt, whichever comes first; call it with
+// interrupts disabled after checking there is nothing left to do, an interrupt that comes
+// in between still wakes it up
+pub fn sleep_until(millis: u64) {
+    interrupt::free(|cs| {
+        if let Some(uptime) = UPTIME.borrow(cs).borrow_mut().as_mut() {
+            let now = uptime.ticks();
+            let wake = (millis * TICKS_PER_SECOND / 1000).min(now + MAX_SLEEP_TICKS);
+            if wake < now + MIN_SLEEP_TICKS {
+                return;
+            }
+
+            let compare = (wake as u32) & COUNTER_MASK;
+            uptime
+                .rtc
+                .set_compare(RtcCompareReg::Compare0, compare)
+                .unwrap();
+            uptime.rtc.get_event_triggered(RtcInterrupt::Compare0, true);
+
+            cortex_m::asm::wfi();
+        }
+    });
+}
+
+// call this from the RTC1 interrupt handler
+pub fn on_interrupt() {
+    interrupt::free(|cs| {
+        if let Some(uptime) = UPTIME.borrow(cs).borrow_mut().as_mut() {
+            uptime.rtc.get_event_triggered(RtcInterrupt::Compare0, true);
+        }
+    });
+}
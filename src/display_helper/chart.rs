@@ -0,0 +1,144 @@
+use arrayvec::ArrayString;
+use core::fmt::Write;
+use embedded_graphics::{
+    geometry::Point, pixelcolor::BinaryColor, prelude::*, primitives::Line, style::PrimitiveStyle,
+};
+
+use super::{draw_small_text, Canvas};
+use crate::{
+    alert::profile::Thresholds,
+    history::{History, CAPACITY},
+};
+
+// the y axis snaps to this and never spans less than two steps
+const PPM_STEP: f32 = 100.0;
+const HOUR_MS: u64 = 3_600_000;
+const LABEL_WIDTH: i32 = 4 * 6 + 2;
+const LABEL_HEIGHT: i32 = 8 + 2;
+
+fn draw_line<D: Canvas>(display: &mut D, from: Point, to: Point) {
+    Line::new(from, to)
+        .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+        .draw(display)
+        .unwrap();
+}
+
+fn draw_dashed_line<D: Canvas>(display: &mut D, y: i32, left: i32, right: i32) {
+    for x in (left..=right).step_by(4) {
+        Pixel(Point::new(x, y), BinaryColor::On)
+            .draw(display)
+            .unwrap();
+    }
+}
+
+fn scale(history: &History, thresholds: &Thresholds) -> (f32, f32) {
+    let (low, high) = history
+        .range()
+        .unwrap_or((thresholds.warning_level_1, thresholds.limit_level));
+
+    let low = libm::floorf(low / PPM_STEP) * PPM_STEP;
+    let high = (libm::ceilf(high / PPM_STEP) * PPM_STEP).max(low + 2.0 * PPM_STEP);
+
+    (low, high)
+}
+
+// plots the history into the rectangle at `top_left`, the y axis labels and time ticks
+// are drawn inside it
+pub fn draw_chart<D: Canvas>(
+    history: &History,
+    thresholds: &Thresholds,
+    top_left: (i32, i32),
+    size: (i32, i32),
+    mut display: D,
+) -> D {
+    let left = top_left.0 + LABEL_WIDTH;
+    let right = top_left.0 + size.0 - 1;
+    let top = top_left.1 + 4;
+    let bottom = top_left.1 + size.1 - 1 - LABEL_HEIGHT;
+    let (width, height) = (right - left, bottom - top);
+
+    if width <= 0 || height <= 0 {
+        return display;
+    }
+
+    let (low, high) = scale(history, thresholds);
+    let to_y = |ppm: f32| bottom - ((ppm - low) / (high - low) * height as f32) as i32;
+
+    draw_line(
+        &mut display,
+        Point::new(left, top),
+        Point::new(left, bottom),
+    );
+    draw_line(
+        &mut display,
+        Point::new(left, bottom),
+        Point::new(right, bottom),
+    );
+
+    let mut label = ArrayString::<[_; 8]>::new();
+    for ppm in [high, low].iter() {
+        label.clear();
+        write!(&mut label, "{:4.0}", ppm).ok();
+        draw_small_text(&mut display, &label, (top_left.0, to_y(*ppm) - 4));
+    }
+
+    let levels = [
+        thresholds.warning_level_1,
+        thresholds.warning_level_2,
+        thresholds.limit_level,
+    ];
+    for level in levels
+        .iter()
+        .filter(|level| **level > low && **level < high)
+    {
+        draw_dashed_line(&mut display, to_y(*level), left + 1, right);
+    }
+
+    // a tick every hour back from now, every 15 minutes for short histories
+    let span_ms = history.span_ms();
+    let tick_ms = if span_ms > 2 * HOUR_MS {
+        HOUR_MS
+    } else {
+        HOUR_MS / 4
+    };
+    let mut tick = tick_ms;
+    while tick < span_ms {
+        let x = right - (tick as f32 / span_ms as f32 * width as f32) as i32;
+        draw_line(
+            &mut display,
+            Point::new(x, bottom),
+            Point::new(x, bottom + 3),
+        );
+        tick += tick_ms;
+    }
+
+    label.clear();
+    write!(
+        &mut label,
+        "-{}h",
+        libm::ceilf(span_ms as f32 / HOUR_MS as f32)
+    )
+    .ok();
+    draw_small_text(&mut display, &label, (left, bottom + 3));
+
+    let samples = history.samples();
+    let step = width as f32 / (CAPACITY - 1) as f32;
+    let to_x = |index: usize| right - ((samples.len() - 1 - index) as f32 * step) as i32;
+
+    let mut previous: Option<Point> = None;
+    for (index, sample) in samples.iter().enumerate() {
+        if sample.is_nan() {
+            previous = None;
+            continue;
+        }
+
+        let point = Point::new(to_x(index), to_y(sample.clamp(low, high)));
+        match previous {
+            Some(from) => draw_line(&mut display, from, point),
+            None => Pixel(point, BinaryColor::On).draw(&mut display).unwrap(),
+        }
+        previous = Some(point);
+    }
+
+    display
+}
@@ -0,0 +1,71 @@
+use embedded_graphics::{
+    geometry::Point, pixelcolor::BinaryColor, prelude::*, primitives::Rectangle,
+    style::PrimitiveStyle,
+};
+
+use super::Canvas;
+
+pub const DIGIT_WIDTH: i32 = 48;
+pub const DIGIT_HEIGHT: i32 = 88;
+// from one digit to the next
+pub const ADVANCE: i32 = 60;
+const THICKNESS: i32 = 10;
+
+// bits a to g: top, top right, bottom right, bottom, bottom left, top left, middle
+const DIGITS: [u8; 10] = [
+    0b011_1111, 0b000_0110, 0b101_1011, 0b100_1111, 0b110_0110, 0b110_1101, 0b111_1101, 0b000_0111,
+    0b111_1111, 0b110_1111,
+];
+const MINUS: u8 = 0b100_0000;
+
+fn segments(c: char) -> u8 {
+    match c {
+        '0'..='9' => DIGITS[c as usize - '0' as usize],
+        '-' => MINUS,
+        // spaces and anything without a segment pattern stay blank
+        _ => 0,
+    }
+}
+
+// top left and bottom right corner of each segment, one pixel apart at the corners
+fn segment_corners(segment: u8) -> ((i32, i32), (i32, i32)) {
+    let (w, h, t) = (DIGIT_WIDTH, DIGIT_HEIGHT, THICKNESS);
+    let middle = (h - t) / 2;
+    let upper = (1, middle + t / 2 - 1);
+    let lower = (middle + t / 2 + 1, h - 2);
+
+    match segment {
+        0 => ((t + 1, 0), (w - t - 2, t - 1)),
+        1 => ((w - t, upper.0), (w - 1, upper.1)),
+        2 => ((w - t, lower.0), (w - 1, lower.1)),
+        3 => ((t + 1, h - t), (w - t - 2, h - 1)),
+        4 => ((0, lower.0), (t - 1, lower.1)),
+        5 => ((0, upper.0), (t - 1, upper.1)),
+        _ => ((t + 1, middle), (w - t - 2, middle + t - 1)),
+    }
+}
+
+// seven segment digits, e.g. for numbers that have to be read across the room
+pub fn draw_digits<D: Canvas>(text: &str, top_left: (i32, i32), mut display: D) -> D {
+    for (index, c) in text.chars().enumerate() {
+        let x = top_left.0 + index as i32 * ADVANCE;
+        let lit = segments(c);
+
+        for segment in 0..7 {
+            if lit & (1 << segment) == 0 {
+                continue;
+            }
+
+            let (from, to) = segment_corners(segment);
+            Rectangle::new(
+                Point::new(x + from.0, top_left.1 + from.1),
+                Point::new(x + to.0, top_left.1 + to.1),
+            )
+            .into_styled(PrimitiveStyle::with_fill(BinaryColor::On))
+            .draw(&mut display)
+            .unwrap();
+        }
+    }
+
+    display
+}
@@ -0,0 +1,114 @@
+use arrayvec::ArrayString;
+use core::fmt::{self, Write};
+
+const INVALID: &str = "---";
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Quantity {
+    Co2,
+    Temperature,
+    Humidity,
+}
+
+impl Quantity {
+    fn decimals(&self) -> usize {
+        match self {
+            Quantity::Co2 | Quantity::Humidity => 0,
+            Quantity::Temperature => 1,
+        }
+    }
+
+    // characters of the number, enough for the SCD30 range in any temperature unit
+    fn width(&self) -> usize {
+        match self {
+            Quantity::Co2 => 5,
+            Quantity::Temperature => 5,
+            Quantity::Humidity => 3,
+        }
+    }
+
+    // a concentration can't be negative, so small negative readings are clamped
+    fn clamp(&self, value: f32) -> f32 {
+        match self {
+            Quantity::Co2 => value.max(0.0),
+            Quantity::Temperature => value,
+            Quantity::Humidity => value.clamp(0.0, 100.0),
+        }
+    }
+}
+
+// a right aligned number of a fixed width, "---" for values that are invalid or too wide;
+// a width in the format string, e.g. "{:6}", replaces the width of the quantity
+pub struct Fixed {
+    value: f32,
+    quantity: Quantity,
+}
+
+pub fn fixed(value: f32, quantity: Quantity) -> Fixed {
+    Fixed { value, quantity }
+}
+
+impl fmt::Display for Fixed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let quantity = self.quantity;
+        let width = f.width().unwrap_or_else(|| quantity.width());
+        let mut number = ArrayString::<[u8; 16]>::new();
+
+        if self.value.is_finite() {
+            let scale = libm::powf(10.0, quantity.decimals() as f32);
+            let mut rounded = libm::roundf(quantity.clamp(self.value) * scale) / scale;
+            // no "-0.0" for values just below zero
+            if rounded == 0.0 {
+                rounded = 0.0;
+            }
+
+            if write!(number, "{:.*}", quantity.decimals(), rounded).is_err()
+                || number.len() > width
+            {
+                number.clear();
+            }
+        }
+
+        if number.is_empty() {
+            number.push_str(INVALID);
+        }
+
+        write!(f, "{:>1$}", number.as_str(), width)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(value: f32, quantity: Quantity) -> ArrayString<[u8; 16]> {
+        let mut text = ArrayString::new();
+        write!(text, "{}", fixed(value, quantity)).unwrap();
+        text
+    }
+
+    #[test]
+    fn numbers_are_right_aligned() {
+        assert_eq!(text(812.4, Quantity::Co2).as_str(), "  812");
+        assert_eq!(text(1234.5, Quantity::Co2).as_str(), " 1235");
+        assert_eq!(text(21.25, Quantity::Temperature).as_str(), " 21.3");
+        assert_eq!(text(-5.04, Quantity::Temperature).as_str(), " -5.0");
+        assert_eq!(text(7.0, Quantity::Humidity).as_str(), "  7");
+    }
+
+    #[test]
+    fn readings_out_of_range_are_clamped() {
+        assert_eq!(text(-3.0, Quantity::Co2).as_str(), "    0");
+        assert_eq!(text(-0.04, Quantity::Temperature).as_str(), "  0.0");
+        assert_eq!(text(100.4, Quantity::Humidity).as_str(), "100");
+    }
+
+    #[test]
+    fn invalid_and_too_wide_numbers_are_dashes() {
+        assert_eq!(text(f32::NAN, Quantity::Co2).as_str(), "  ---");
+        assert_eq!(text(f32::INFINITY, Quantity::Temperature).as_str(), "  ---");
+        assert_eq!(text(f32::NAN, Quantity::Humidity).as_str(), "---");
+        assert_eq!(text(123_456.0, Quantity::Co2).as_str(), "  ---");
+        assert_eq!(text(-1234.0, Quantity::Temperature).as_str(), "  ---");
+    }
+}
@@ -0,0 +1,387 @@
+// Renders frames on the host and compares them against the PBM images in `golden/`.
+// Run `UPDATE_GOLDEN=1 cargo test --lib --target <host triple>` to accept a new layout.
+extern crate std;
+
+use std::{env, fs, path::PathBuf, string::String, vec::Vec};
+
+use embedded_graphics::prelude::*;
+use epd_waveshare::{epd2in9::Display2in9, epd4in2::Display4in2, prelude::*};
+
+use super::{
+    chart::draw_chart,
+    clear_numbers, draw_numbers,
+    format::Quantity,
+    icons::{draw_icon, Icon},
+    menu::draw_menu,
+    screen::{Layout, Page, Redraw, ScreenData, ScreenManager},
+    Canvas,
+};
+use crate::{
+    alert::{escalation::AlertLevel, profile::Profile},
+    clock::{DateTime, TimeOfDay, Weekday},
+    history::History,
+    number_representations::Unit,
+    scd30::SensorData,
+    settings::{
+        menu::{Menu, MenuInput},
+        Settings,
+    },
+    ssd1306::{self, Display128x64},
+    statistics::ReadingStatistics,
+    ventilation::Advice,
+};
+
+// the frame buffers of the panels as a binary PBM, where a set bit is black and each row
+// starts on a new byte
+pub trait ToPbm {
+    fn to_pbm(&self) -> Vec<u8>;
+}
+
+fn pbm_header(size: Size) -> Vec<u8> {
+    format!("P4\n{} {}\n", size.width, size.height).into_bytes()
+}
+
+// the e-paper buffers are already in rows, but a set bit is white there
+fn epd_to_pbm(size: Size, buffer: &[u8]) -> Vec<u8> {
+    let mut pbm = pbm_header(size);
+    pbm.extend(buffer.iter().map(|byte| !byte));
+
+    pbm
+}
+
+impl ToPbm for Display4in2 {
+    fn to_pbm(&self) -> Vec<u8> {
+        epd_to_pbm(self.size(), self.buffer())
+    }
+}
+
+impl ToPbm for Display2in9 {
+    fn to_pbm(&self) -> Vec<u8> {
+        epd_to_pbm(self.size(), self.buffer())
+    }
+}
+
+// the OLED buffer has a byte per column of each page, a set bit is lit
+impl ToPbm for Display128x64 {
+    fn to_pbm(&self) -> Vec<u8> {
+        let mut pbm = pbm_header(self.size());
+        let buffer = self.buffer();
+
+        for y in 0..ssd1306::HEIGHT {
+            let page = &buffer[(y / 8 * ssd1306::WIDTH) as usize..][..ssd1306::WIDTH as usize];
+
+            for columns in page.chunks(8) {
+                let bits = columns
+                    .iter()
+                    .enumerate()
+                    .fold(0u8, |bits, (index, column)| {
+                        bits | (column >> (y % 8) & 1) << (7 - index)
+                    });
+                pbm.push(bits);
+            }
+        }
+
+        pbm
+    }
+}
+
+fn golden_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("src/display_helper/golden")
+        .join(format!("{}.pbm", name))
+}
+
+pub fn assert_golden<D: ToPbm>(name: &str, display: &D) {
+    let actual = display.to_pbm();
+    let path = golden_path(name);
+    let output = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("target/golden");
+    let actual_path = output.join(format!("{}.pbm", name));
+
+    if env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::write(&path, &actual).unwrap();
+    }
+
+    let expected = fs::read(&path).unwrap_or_default();
+    if expected == actual {
+        // the frame of an earlier failure is out of date
+        fs::remove_file(&actual_path).ok();
+        return;
+    }
+
+    let differing: u32 = expected
+        .iter()
+        .zip(actual.iter())
+        .map(|(expected, actual)| (expected ^ actual).count_ones())
+        .sum();
+
+    fs::create_dir_all(&output).unwrap();
+    fs::write(&actual_path, &actual).unwrap();
+
+    let message: String = if expected.is_empty() {
+        format!("no golden image at {}", path.display())
+    } else {
+        format!("{} pixels differ from {}", differing, path.display())
+    };
+    panic!(
+        "{}, the frame was written to {}",
+        message,
+        actual_path.display()
+    );
+}
+
+struct Fixture {
+    history: History,
+    statistics: ReadingStatistics,
+}
+
+// a stuffy afternoon, aired twice, warming up while the air dries out
+fn fixture() -> Fixture {
+    let mut history = History::init(4 * 60 * 60 * 1000);
+    let mut statistics = ReadingStatistics::init();
+
+    for half_minute in 0..480 {
+        let minutes = half_minute as f32 / 2.0;
+        let millis = half_minute * 30_000;
+        let reading = SensorData {
+            co2: 450.0 + (half_minute / 2 % 100) as f32 * 9.0,
+            temperature: 20.0 + minutes * 0.01,
+            humidity: 60.0 - minutes * 0.15,
+        };
+
+        if half_minute % 2 == 0 {
+            history.update(millis, reading.co2);
+        }
+        statistics.update(millis, Weekday::Monday, &reading);
+    }
+
+    Fixture {
+        history,
+        statistics,
+    }
+}
+
+impl Fixture {
+    fn data(&self) -> ScreenData<'_> {
+        ScreenData {
+            co2: 812.5,
+            temperature: 21.3,
+            temperature_unit: Unit::Celsius,
+            humidity: 45.25,
+            twa: 640.0,
+            stel: 905.75,
+            advice: Advice::Ventilate(12),
+            alert_level: AlertLevel::High,
+            silenced: false,
+            sensor_error: false,
+            history: &self.history,
+            statistics: &self.statistics,
+            thresholds: Profile::Standard.thresholds(),
+            profile: Profile::Standard.name(),
+            firmware_version: [3, 66],
+            measurement_interval: 2,
+            temperature_offset: 150,
+            uptime_ms: 90 * 60_000,
+        }
+    }
+}
+
+fn render_page(page: Page, unit: Unit) -> Display4in2 {
+    let fixture = fixture();
+    let mut screens = ScreenManager::init();
+    screens.show(page);
+
+    let mut data = fixture.data();
+    data.temperature_unit = unit;
+    let (display, redraw) = screens.render(Display4in2::default(), &data);
+    assert!(redraw == Redraw::Full);
+
+    display
+}
+
+#[test]
+fn live_page() {
+    assert_golden("live_page", &render_page(Page::Live, Unit::Celsius));
+}
+
+#[test]
+fn live_page_fahrenheit() {
+    assert_golden(
+        "live_page_fahrenheit",
+        &render_page(Page::Live, Unit::Fahrenheit),
+    );
+}
+
+#[test]
+fn live_page_big_digits() {
+    let fixture = fixture();
+    let mut screens = ScreenManager::init();
+    screens.set_layout(Layout::BigDigits);
+
+    let (display, _) = screens.render(Display4in2::default(), &fixture.data());
+    assert_golden("live_page_big_digits", &display);
+}
+
+#[test]
+fn history_page() {
+    assert_golden("history_page", &render_page(Page::History, Unit::Celsius));
+}
+
+#[test]
+fn statistics_page() {
+    assert_golden(
+        "statistics_page",
+        &render_page(Page::Statistics, Unit::Celsius),
+    );
+}
+
+#[test]
+fn diagnostics_page() {
+    assert_golden(
+        "diagnostics_page",
+        &render_page(Page::Diagnostics, Unit::Celsius),
+    );
+}
+
+#[test]
+fn unchanged_data_redraws_nothing() {
+    let fixture = fixture();
+    let mut screens = ScreenManager::init();
+
+    let (display, _) = screens.render(Display4in2::default(), &fixture.data());
+    let (display, redraw) = screens.render(display, &fixture.data());
+    assert!(redraw == Redraw::Nothing);
+
+    let mut changed = fixture.data();
+    changed.co2 = 1234.0;
+    let (_, redraw) = screens.render(display, &changed);
+    assert!(redraw == Redraw::Region((220, 90), (387, 105)));
+}
+
+fn open_menu() -> Menu {
+    let now = DateTime {
+        weekday: Weekday::Tuesday,
+        time: TimeOfDay::new(8, 30),
+        second: 12,
+    };
+
+    Menu::open(Settings::default(), now)
+}
+
+#[test]
+fn settings_menu() {
+    let mut menu = open_menu();
+    menu.handle(MenuInput::Down);
+    menu.handle(MenuInput::Select);
+    menu.handle(MenuInput::Up);
+
+    assert_golden(
+        "settings_menu",
+        &draw_menu(&menu, Unit::Celsius, Display4in2::default()),
+    );
+}
+
+#[test]
+fn numbers_and_chart() {
+    let fixture = fixture();
+    let mut display = Display4in2::default();
+
+    display = draw_numbers(1234.5, Quantity::Co2, "ppm", (20, 20), display);
+    display = draw_numbers(21.25, Quantity::Temperature, "°C", (20, 40), display);
+    display = draw_numbers(-0.04, Quantity::Temperature, "°C", (20, 60), display);
+    // invalid readings and values too wide for the field
+    display = draw_numbers(f32::NAN, Quantity::Co2, "ppm", (20, 80), display);
+    display = draw_numbers(-12345.67, Quantity::Temperature, "°C", (20, 100), display);
+    display = draw_numbers(123456.0, Quantity::Co2, "ppm", (20, 120), display);
+    display = draw_numbers(-3.0, Quantity::Humidity, "%", (200, 20), display);
+    display = draw_numbers(99.0, Quantity::Humidity, "%", (200, 40), display);
+    display = clear_numbers(display, (200, 40), (380, 55));
+    display = draw_chart(
+        &fixture.history,
+        &Profile::Uba.thresholds(),
+        (200, 150),
+        (200, 150),
+        display,
+    );
+
+    assert_golden("numbers_and_chart", &display);
+}
+
+#[test]
+fn icons() {
+    let icons = [
+        Icon::Smiley,
+        Icon::Neutral,
+        Icon::Frown,
+        Icon::OpenWindow,
+        Icon::Warning,
+        Icon::Snooze,
+        Icon::SensorError,
+    ];
+
+    let mut display = Display4in2::default();
+    for (index, icon) in icons.iter().enumerate() {
+        display = draw_icon(*icon, (20 + index as i32 * 48, 20), display);
+    }
+
+    assert_golden("icons", &display);
+}
+
+// the OLED and the 2.9" e-paper
+fn compact_pages<D: Canvas + ToPbm>(display: D) {
+    let fixture = fixture();
+    let size = display.size();
+
+    let mut screens = ScreenManager::init();
+    let (display, redraw) = screens.render(display, &fixture.data());
+    assert!(redraw == Redraw::Full);
+    assert_golden(
+        &format!("compact_live_{}x{}", size.width, size.height),
+        &display,
+    );
+
+    screens.next_page();
+    assert!(screens.page() == Page::History);
+    let (display, _) = screens.render(display, &fixture.data());
+    assert_golden(
+        &format!("compact_history_{}x{}", size.width, size.height),
+        &display,
+    );
+
+    screens.next_page();
+    assert!(screens.page() == Page::Live);
+}
+
+#[test]
+fn compact_pages_oled() {
+    compact_pages(Display128x64::default());
+}
+
+#[test]
+fn compact_pages_epd2in9() {
+    compact_pages(Display2in9::default());
+}
+
+fn compact_settings_menu<D: Canvas + ToPbm>(display: D) {
+    let mut menu = open_menu();
+    for _ in 0..5 {
+        menu.handle(MenuInput::Down);
+    }
+    menu.handle(MenuInput::Select);
+
+    let size = display.size();
+    let display = draw_menu(&menu, Unit::Celsius, display);
+    assert_golden(
+        &format!("compact_menu_{}x{}", size.width, size.height),
+        &display,
+    );
+}
+
+#[test]
+fn compact_settings_menu_oled() {
+    compact_settings_menu(Display128x64::default());
+}
+
+#[test]
+fn compact_settings_menu_epd2in9() {
+    compact_settings_menu(Display2in9::default());
+}
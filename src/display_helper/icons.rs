@@ -0,0 +1,378 @@
+use embedded_graphics::{geometry::Point, pixelcolor::BinaryColor, prelude::*};
+
+use super::Canvas;
+use crate::alert::escalation::AlertLevel;
+
+pub const ICON_SIZE: i32 = 32;
+pub const ARROW_SIZE: i32 = 16;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Icon {
+    Smiley,
+    Neutral,
+    Frown,
+    OpenWindow,
+    Warning,
+    Snooze,
+    SensorError,
+}
+
+impl Icon {
+    // a face for the CO2 level and a warning once the limit is passed
+    pub fn for_alert_level(level: AlertLevel) -> Icon {
+        match level {
+            AlertLevel::Good => Icon::Smiley,
+            AlertLevel::Elevated => Icon::Neutral,
+            AlertLevel::High => Icon::Frown,
+            AlertLevel::Limit => Icon::Warning,
+        }
+    }
+
+    fn bitmap(&self) -> &'static [u32; 32] {
+        match self {
+            Icon::Smiley => &SMILEY,
+            Icon::Neutral => &NEUTRAL,
+            Icon::Frown => &FROWN,
+            Icon::OpenWindow => &OPEN_WINDOW,
+            Icon::Warning => &WARNING,
+            Icon::Snooze => &SNOOZE,
+            Icon::SensorError => &SENSOR_ERROR,
+        }
+    }
+}
+
+// only the set bits are drawn, the area is expected to be cleared
+fn draw_bitmap<D: Canvas>(rows: &[u32], width: i32, top_left: (i32, i32), mut display: D) -> D {
+    let pixels = rows.iter().enumerate().flat_map(|(y, row)| {
+        (0..width)
+            .filter(move |x| row & (1 << (width - 1 - x)) != 0)
+            .map(move |x| {
+                Pixel(
+                    Point::new(top_left.0 + x, top_left.1 + y as i32),
+                    BinaryColor::On,
+                )
+            })
+    });
+    display.draw_iter(pixels).unwrap();
+
+    display
+}
+
+pub fn draw_icon<D: Canvas>(icon: Icon, top_left: (i32, i32), display: D) -> D {
+    draw_bitmap(icon.bitmap(), ICON_SIZE, top_left, display)
+}
+
+// the arrow for a statistics::Trend symbol, nothing for any other character
+pub fn draw_arrow<D: Canvas>(symbol: char, top_left: (i32, i32), display: D) -> D {
+    let bitmap = match symbol {
+        '^' => &RISING,
+        '>' => &STEADY,
+        'v' => &FALLING,
+        _ => return display,
+    };
+
+    draw_bitmap(bitmap, ARROW_SIZE, top_left, display)
+}
+
+// one row per u32, the highest bit of the width is the leftmost pixel
+const SMILEY: [u32; 32] = [
+    0b00000000_00000000_00000000_00000000,
+    0b00000000_00011111_11111000_00000000,
+    0b00000000_11111111_11111111_00000000,
+    0b00000001_11100000_00000111_10000000,
+    0b00000011_10000000_00000001_11000000,
+    0b00000111_00000000_00000000_11100000,
+    0b00001110_00000000_00000000_01110000,
+    0b00011100_00000000_00000000_00111000,
+    0b00111000_00000000_00000000_00011100,
+    0b00110000_00000000_00000000_00001100,
+    0b00110000_01111000_00011110_00001100,
+    0b01100000_01111000_00011110_00000110,
+    0b01100000_01111000_00011110_00000110,
+    0b01100000_01111000_00011110_00000110,
+    0b01100000_00000000_00000000_00000110,
+    0b01100000_00000000_00000000_00000110,
+    0b01100000_00000000_00000000_00000110,
+    0b01100000_00000000_00000000_00000110,
+    0b01100000_00000000_00000000_00000110,
+    0b01100000_11100000_00000111_00000110,
+    0b01100000_01110000_00001110_00000110,
+    0b00110000_00111100_00111100_00001100,
+    0b00110000_00011111_11111000_00001100,
+    0b00111000_00000111_11100000_00011100,
+    0b00011100_00000000_00000000_00111000,
+    0b00001110_00000000_00000000_01110000,
+    0b00000111_00000000_00000000_11100000,
+    0b00000011_10000000_00000001_11000000,
+    0b00000001_11100000_00000111_10000000,
+    0b00000000_11111111_11111111_00000000,
+    0b00000000_00011111_11111000_00000000,
+    0b00000000_00000000_00000000_00000000,
+];
+
+const NEUTRAL: [u32; 32] = [
+    0b00000000_00000000_00000000_00000000,
+    0b00000000_00011111_11111000_00000000,
+    0b00000000_11111111_11111111_00000000,
+    0b00000001_11100000_00000111_10000000,
+    0b00000011_10000000_00000001_11000000,
+    0b00000111_00000000_00000000_11100000,
+    0b00001110_00000000_00000000_01110000,
+    0b00011100_00000000_00000000_00111000,
+    0b00111000_00000000_00000000_00011100,
+    0b00110000_00000000_00000000_00001100,
+    0b00110000_01111000_00011110_00001100,
+    0b01100000_01111000_00011110_00000110,
+    0b01100000_01111000_00011110_00000110,
+    0b01100000_01111000_00011110_00000110,
+    0b01100000_00000000_00000000_00000110,
+    0b01100000_00000000_00000000_00000110,
+    0b01100000_00000000_00000000_00000110,
+    0b01100000_00000000_00000000_00000110,
+    0b01100000_00000000_00000000_00000110,
+    0b01100000_00000000_00000000_00000110,
+    0b01100000_00000000_00000000_00000110,
+    0b00110000_00111111_11111110_00001100,
+    0b00110000_00111111_11111110_00001100,
+    0b00111000_00000000_00000000_00011100,
+    0b00011100_00000000_00000000_00111000,
+    0b00001110_00000000_00000000_01110000,
+    0b00000111_00000000_00000000_11100000,
+    0b00000011_10000000_00000001_11000000,
+    0b00000001_11100000_00000111_10000000,
+    0b00000000_11111111_11111111_00000000,
+    0b00000000_00011111_11111000_00000000,
+    0b00000000_00000000_00000000_00000000,
+];
+
+const FROWN: [u32; 32] = [
+    0b00000000_00000000_00000000_00000000,
+    0b00000000_00011111_11111000_00000000,
+    0b00000000_11111111_11111111_00000000,
+    0b00000001_11100000_00000111_10000000,
+    0b00000011_10000000_00000001_11000000,
+    0b00000111_00000000_00000000_11100000,
+    0b00001110_00000000_00000000_01110000,
+    0b00011100_00000000_00000000_00111000,
+    0b00111000_00000000_00000000_00011100,
+    0b00110000_00000000_00000000_00001100,
+    0b00110000_01111000_00011110_00001100,
+    0b01100000_01111000_00011110_00000110,
+    0b01100000_01111000_00011110_00000110,
+    0b01100000_01111000_00011110_00000110,
+    0b01100000_00000000_00000000_00000110,
+    0b01100000_00000000_00000000_00000110,
+    0b01100000_00000000_00000000_00000110,
+    0b01100000_00000000_00000000_00000110,
+    0b01100000_00000000_00000000_00000110,
+    0b01100000_00000000_00000000_00000110,
+    0b01100000_00000111_11100000_00000110,
+    0b00110000_00011111_11111000_00001100,
+    0b00110000_00111100_00111100_00001100,
+    0b00111000_01110000_00001110_00011100,
+    0b00011100_00100000_00000100_00111000,
+    0b00001110_00000000_00000000_01110000,
+    0b00000111_00000000_00000000_11100000,
+    0b00000011_10000000_00000001_11000000,
+    0b00000001_11100000_00000111_10000000,
+    0b00000000_11111111_11111111_00000000,
+    0b00000000_00011111_11111000_00000000,
+    0b00000000_00000000_00000000_00000000,
+];
+
+const WARNING: [u32; 32] = [
+    0b00000000_00000000_00000000_00000000,
+    0b00000000_00000000_00000000_00000000,
+    0b00000000_00000001_10000000_00000000,
+    0b00000000_00000011_11000000_00000000,
+    0b00000000_00000011_11000000_00000000,
+    0b00000000_00000111_11100000_00000000,
+    0b00000000_00000110_01100000_00000000,
+    0b00000000_00001110_01110000_00000000,
+    0b00000000_00001100_00110000_00000000,
+    0b00000000_00011100_00111000_00000000,
+    0b00000000_00011000_00011000_00000000,
+    0b00000000_00111001_10011100_00000000,
+    0b00000000_00110001_10001100_00000000,
+    0b00000000_01110001_10001110_00000000,
+    0b00000000_01100001_10000110_00000000,
+    0b00000000_11100001_10000111_00000000,
+    0b00000001_11000001_10000011_10000000,
+    0b00000001_11000001_10000011_10000000,
+    0b00000011_10000001_10000001_11000000,
+    0b00000011_00000001_10000000_11000000,
+    0b00000111_00000001_10000000_11100000,
+    0b00000110_00000001_10000000_01100000,
+    0b00001110_00000000_00000000_01110000,
+    0b00001100_00000000_00000000_00110000,
+    0b00011100_00000001_10000000_00111000,
+    0b00011000_00000001_10000000_00011000,
+    0b00111000_00000000_00000000_00011100,
+    0b00110000_00000000_00000000_00001100,
+    0b01111111_11111111_11111111_11111110,
+    0b11111111_11111111_11111111_11111111,
+    0b00000000_00000000_00000000_00000000,
+    0b00000000_00000000_00000000_00000000,
+];
+
+const OPEN_WINDOW: [u32; 32] = [
+    0b00000000_00000000_00000000_00000000,
+    0b00000000_00000000_00000000_00000000,
+    0b00000000_00000000_00000000_00000000,
+    0b00111111_11111111_11111100_00000000,
+    0b00111111_11111111_11111111_10000000,
+    0b00110000_01100000_11000111_11110000,
+    0b00110000_01100000_11000000_11111000,
+    0b00110000_01100000_11000000_00011000,
+    0b00110000_01100000_11000000_00011000,
+    0b00110000_01100000_11000000_00011000,
+    0b00110000_01100000_11000000_00011000,
+    0b00110000_01100000_11000000_00011000,
+    0b00110000_01100000_11000000_00011000,
+    0b00110000_01100000_11000000_00011000,
+    0b00110000_01100000_11000000_00011000,
+    0b00111111_11111111_11000000_00011000,
+    0b00110000_01100000_11000000_00011000,
+    0b00110000_01100000_11000000_00011000,
+    0b00110000_01100000_11000000_00011000,
+    0b00110000_01100000_11000000_00011000,
+    0b00110000_01100000_11000000_00011000,
+    0b00110000_01100000_11000000_00011000,
+    0b00110000_01100000_11000000_00011000,
+    0b00110000_01100000_11000000_11111000,
+    0b00110000_01100000_11000111_11111000,
+    0b00110000_01100000_11111111_10000000,
+    0b00110000_01100000_11111100_00000000,
+    0b00111111_11111111_11110000_00000000,
+    0b00111111_11111111_11000000_00000000,
+    0b00000000_00000000_00000000_00000000,
+    0b00000000_00000000_00000000_00000000,
+    0b00000000_00000000_00000000_00000000,
+];
+
+const SNOOZE: [u32; 32] = [
+    0b00000000_00000000_00000000_00000000,
+    0b00000000_00000000_00000000_00000000,
+    0b00000000_00000000_00000001_11111110,
+    0b00000000_00000110_00000001_11111110,
+    0b00000000_00000110_00000000_00011110,
+    0b00000000_00011111_10000000_00111000,
+    0b00000000_01111111_11100000_01111000,
+    0b00000000_11110000_11110000_11110000,
+    0b00000001_11000000_00111000_11100000,
+    0b00000001_10000000_00011001_11000000,
+    0b00000011_10000000_00011101_11111110,
+    0b00000011_00000000_00001101_11111110,
+    0b00000011_00000000_00001100_00000000,
+    0b00000011_00000000_00001100_00000000,
+    0b00000011_00000000_00001100_00000000,
+    0b00000011_00000000_00001100_00000000,
+    0b00000011_00000000_00001100_00000000,
+    0b00000011_00000000_00001100_00000000,
+    0b00000011_00000000_00001100_00000000,
+    0b00000011_00000000_00001100_00000000,
+    0b00000011_00000000_00001100_00000000,
+    0b00000011_00000000_00001100_00000000,
+    0b00000011_00000000_00001100_00000000,
+    0b00011111_11111111_11111111_10000000,
+    0b00011111_11111111_11111111_10000000,
+    0b00000000_00001111_00000000_00000000,
+    0b00000000_00001111_00000000_00000000,
+    0b00000000_00001111_00000000_00000000,
+    0b00000000_00001111_00000000_00000000,
+    0b00000000_00000000_00000000_00000000,
+    0b00000000_00000000_00000000_00000000,
+    0b00000000_00000000_00000000_00000000,
+];
+
+const SENSOR_ERROR: [u32; 32] = [
+    0b00000000_00000000_00000000_00000000,
+    0b00000000_00110001_10001100_00000000,
+    0b00000000_00110001_10001100_00000000,
+    0b00000000_00110001_10001100_00000000,
+    0b00000000_00110001_10001100_00000000,
+    0b00000000_00110001_10001100_00000000,
+    0b00000011_11111111_11111111_11000000,
+    0b00000011_11111111_11111111_11000000,
+    0b00000011_00000000_00000000_11000000,
+    0b00000011_00000000_00000000_11000000,
+    0b01111111_00000000_00000000_11111110,
+    0b01111111_00110000_00001100_11111110,
+    0b00000011_00011000_00011000_11000000,
+    0b00000011_00001100_00110000_11000000,
+    0b00000011_00000110_01100000_11000000,
+    0b01111111_00000011_11000000_11111110,
+    0b01111111_00000001_10000000_11111110,
+    0b00000011_00000011_11000000_11000000,
+    0b00000011_00000110_01100000_11000000,
+    0b00000011_00001100_00110000_11000000,
+    0b01111111_00011000_00011000_11111110,
+    0b01111111_00000000_00000000_11111110,
+    0b00000011_00000000_00000000_11000000,
+    0b00000011_00000000_00000000_11000000,
+    0b00000011_11111111_11111111_11000000,
+    0b00000011_11111111_11111111_11000000,
+    0b00000000_00110001_10001100_00000000,
+    0b00000000_00110001_10001100_00000000,
+    0b00000000_00110001_10001100_00000000,
+    0b00000000_00110001_10001100_00000000,
+    0b00000000_00110001_10001100_00000000,
+    0b00000000_00000000_00000000_00000000,
+];
+
+const RISING: [u32; 16] = [
+    0b00000000_00000000,
+    0b00000000_00000000,
+    0b00000000_00000000,
+    0b00001111_11110000,
+    0b00000111_11110000,
+    0b00000011_11110000,
+    0b00000001_11110000,
+    0b00000011_11110000,
+    0b00000111_01110000,
+    0b00001110_00110000,
+    0b00011100_00010000,
+    0b00111000_00000000,
+    0b00010000_00000000,
+    0b00000000_00000000,
+    0b00000000_00000000,
+    0b00000000_00000000,
+];
+
+const STEADY: [u32; 16] = [
+    0b00000000_00000000,
+    0b00000000_00000000,
+    0b00000000_10000000,
+    0b00000000_11000000,
+    0b00000000_11100000,
+    0b00000000_11110000,
+    0b01111111_11111000,
+    0b01111111_11111100,
+    0b01111111_11111000,
+    0b00000000_11110000,
+    0b00000000_11100000,
+    0b00000000_11000000,
+    0b00000000_10000000,
+    0b00000000_00000000,
+    0b00000000_00000000,
+    0b00000000_00000000,
+];
+
+const FALLING: [u32; 16] = [
+    0b00000000_00000000,
+    0b00000000_00000000,
+    0b00010000_00000000,
+    0b00111000_00000000,
+    0b00011100_00010000,
+    0b00001110_00110000,
+    0b00000111_01110000,
+    0b00000011_11110000,
+    0b00000001_11110000,
+    0b00000011_11110000,
+    0b00000111_11110000,
+    0b00001111_11110000,
+    0b00000000_00000000,
+    0b00000000_00000000,
+    0b00000000_00000000,
+    0b00000000_00000000,
+];
@@ -0,0 +1,135 @@
+use arrayvec::ArrayString;
+use embedded_graphics::{geometry::Size, pixelcolor::BinaryColor};
+
+use super::{draw_large_text, draw_mid_text, draw_small_text, Canvas};
+use crate::{
+    number_representations::Unit,
+    settings::menu::{Item, Menu, ITEMS},
+};
+
+const FIRST_ROW: i32 = 52;
+const ROW_HEIGHT: i32 = 19;
+const HELP_ROW: i32 = 280;
+// the menu scrolls once the items don't fit
+const FULL_SIZE: (u32, u32) = (400, 300);
+const COMPACT_FIRST_ROW: i32 = 12;
+const COMPACT_ROW_HEIGHT: i32 = 10;
+const COMPACT_CHAR_WIDTH: i32 = 6;
+const COMPACT_NAME_COLUMN: i32 = 6;
+
+// the row and the index of each item that fits, the selected item stays on the last row
+// while scrolling down
+fn visible_items(menu: &Menu, rows: usize) -> impl Iterator<Item = (usize, (usize, &Item))> {
+    let first = (menu.selected() + 1).saturating_sub(rows.max(1));
+    ITEMS
+        .iter()
+        .enumerate()
+        .skip(first)
+        .take(rows.max(1))
+        .enumerate()
+}
+
+// the menu takes the whole panel
+pub fn draw_menu<D: Canvas>(menu: &Menu, unit: Unit, mut display: D) -> D {
+    let size = display.size();
+    display.clear(BinaryColor::Off).unwrap();
+
+    if size.width < FULL_SIZE.0 || size.height < FULL_SIZE.1 {
+        draw_compact_menu(menu, unit, size, &mut display);
+        return display;
+    }
+
+    draw_large_text(&mut display, "Settings", (20, 10));
+
+    let rows = ((HELP_ROW - FIRST_ROW) / ROW_HEIGHT) as usize;
+    let mut value = ArrayString::<[_; 24]>::new();
+
+    for (row, (index, item)) in visible_items(menu, rows) {
+        let y = FIRST_ROW + row as i32 * ROW_HEIGHT;
+        let selected = index == menu.selected();
+
+        if selected {
+            draw_mid_text(&mut display, ">", (8, y));
+        }
+        draw_mid_text(&mut display, item.name(), (24, y));
+
+        value.clear();
+        if selected && menu.is_editing() {
+            value.push_str("< ");
+            item.write_value(menu.value(index), unit, &mut value).ok();
+            value.push_str(" >");
+        } else {
+            item.write_value(menu.value(index), unit, &mut value).ok();
+        }
+        draw_mid_text(&mut display, &value, (200, y));
+    }
+
+    let help = if menu.is_editing() {
+        "1 +  2 -  3 ok  4 cancel"
+    } else {
+        "1 up  2 down  3 edit  4 exit"
+    };
+    draw_mid_text(&mut display, help, (20, HELP_ROW));
+
+    display
+}
+
+// the x of a value right aligned to the edge of the panel
+fn compact_value_column(width: i32, value: &str) -> i32 {
+    width - value.chars().count() as i32 * COMPACT_CHAR_WIDTH
+}
+
+// without the help line, "*" marks the item being edited; the values are right aligned so
+// long names don't run into them
+fn draw_compact_menu<D: Canvas>(menu: &Menu, unit: Unit, size: Size, display: &mut D) {
+    let (width, height) = (size.width as i32, size.height as i32);
+    draw_small_text(display, "Settings", (0, 0));
+
+    let rows = ((height - COMPACT_FIRST_ROW) / COMPACT_ROW_HEIGHT) as usize;
+    let mut value = ArrayString::<[_; 24]>::new();
+
+    for (row, (index, item)) in visible_items(menu, rows) {
+        let y = COMPACT_FIRST_ROW + row as i32 * COMPACT_ROW_HEIGHT;
+
+        if index == menu.selected() {
+            let marker = if menu.is_editing() { "*" } else { ">" };
+            draw_small_text(display, marker, (0, y));
+        }
+        draw_small_text(display, item.name(), (COMPACT_NAME_COLUMN, y));
+
+        value.clear();
+        item.write_value(menu.value(index), unit, &mut value).ok();
+        draw_small_text(display, &value, (compact_value_column(width, &value), y));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::Settings;
+
+    #[test]
+    fn compact_names_and_values_leave_a_gap() {
+        let mut value = ArrayString::<[_; 24]>::new();
+
+        for item in ITEMS.iter() {
+            let name_end = COMPACT_NAME_COLUMN + item.name().len() as i32 * COMPACT_CHAR_WIDTH;
+            let (min, max) = item.range(&Settings::default());
+
+            for unit in [Unit::Celsius, Unit::Fahrenheit, Unit::Kelvin].iter() {
+                for raw in [min, max].iter() {
+                    value.clear();
+                    item.write_value(*raw, *unit, &mut value).unwrap();
+
+                    let column = compact_value_column(128, &value);
+                    assert!(
+                        column >= name_end + COMPACT_CHAR_WIDTH,
+                        "{} runs into {}",
+                        item.name(),
+                        value
+                    );
+                }
+            }
+        }
+    }
+}
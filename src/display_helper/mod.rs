@@ -1,8 +1,8 @@
 use arrayvec::ArrayString;
-use core::fmt::Write;
+use core::{convert::Infallible, fmt::Write};
 use embedded_graphics::{
     egtext,
-    fonts::{Font, Font12x16, Font24x32},
+    fonts::{Font, Font12x16, Font24x32, Font6x8},
     geometry::Point,
     pixelcolor::BinaryColor,
     prelude::*,
@@ -10,9 +10,26 @@ use embedded_graphics::{
     style::PrimitiveStyle,
     text_style,
 };
-use epd_waveshare::epd4in2::*;
 
-fn draw_text<F>(display: &mut Display4in2, text: &str, position: (i32, i32), font: F) -> ()
+use format::{fixed, Quantity};
+
+pub mod chart;
+pub mod digits;
+pub mod format;
+#[cfg(test)]
+mod golden;
+pub mod icons;
+pub mod menu;
+pub mod panel;
+pub mod refresh;
+pub mod screen;
+
+// the frame buffer of any panel, drawing into memory can't fail
+pub trait Canvas: DrawTarget<BinaryColor, Error = Infallible> {}
+
+impl<D> Canvas for D where D: DrawTarget<BinaryColor, Error = Infallible> {}
+
+fn draw_text<F, D: Canvas>(display: &mut D, text: &str, position: (i32, i32), font: F)
 where
     F: Font + Clone + Copy,
 {
@@ -25,33 +42,29 @@ where
     .unwrap();
 }
 
-fn draw_large_text(display: &mut Display4in2, text: &str, position: (i32, i32)) -> () {
+fn draw_large_text<D: Canvas>(display: &mut D, text: &str, position: (i32, i32)) {
     draw_text(display, text, position, Font24x32);
 }
 
-fn draw_mid_text(display: &mut Display4in2, text: &str, position: (i32, i32)) -> () {
+fn draw_mid_text<D: Canvas>(display: &mut D, text: &str, position: (i32, i32)) {
     draw_text(display, text, position, Font12x16);
 }
 
-pub fn draw_titles(mut display: Display4in2) -> Display4in2 {
-    draw_large_text(&mut display, "Air Quality", (20, 30));
-
-    draw_mid_text(&mut display, "Carbon Dioxide:", (20, 90));
-    draw_mid_text(&mut display, "Temperature:", (20, 130));
-    draw_mid_text(&mut display, "Humidity:", (20, 170));
-
-    display
+fn draw_small_text<D: Canvas>(display: &mut D, text: &str, position: (i32, i32)) {
+    draw_text(display, text, position, Font6x8);
 }
 
-pub fn draw_numbers(
+pub fn draw_numbers<D: Canvas>(
     value: f32,
+    quantity: Quantity,
     unit: &str,
     position: (i32, i32),
-    mut display: Display4in2,
-) -> Display4in2 {
-    let mut buf = ArrayString::<[_; 12]>::new();
+    mut display: D,
+) -> D {
+    let mut buf = ArrayString::<[_; 16]>::new();
 
-    write!(&mut buf, "{:.2} {}", value, unit).expect("Failed to write to buffer");
+    // a unit that doesn't fit is cut off
+    write!(&mut buf, "{} {}", fixed(value, quantity), unit).ok();
 
     egtext!(
         text = &buf,
@@ -64,11 +77,11 @@ pub fn draw_numbers(
     display
 }
 
-pub fn clear_numbers(
-    mut display: Display4in2,
+pub fn clear_numbers<D: Canvas>(
+    mut display: D,
     top_left: (i32, i32),
     bottom_right: (i32, i32),
-) -> Display4in2 {
+) -> D {
     Rectangle::new(
         Point::new(top_left.0, top_left.1),
         Point::new(bottom_right.0, bottom_right.1),
@@ -0,0 +1,240 @@
+use embedded_hal::{
+    blocking::{delay::DelayMs, i2c, spi::Write},
+    digital::v2::{InputPin, OutputPin},
+};
+use epd_waveshare::{
+    epd2in9::{Display2in9, EPD2in9},
+    epd4in2::{Display4in2, EPD4in2},
+    prelude::*,
+};
+
+use super::{refresh::Refresh, Canvas};
+use crate::ssd1306::{self, Display128x64, SSD1306};
+
+// the driver side of a display, the layouts are drawn on its frame buffer
+pub trait Panel {
+    type Display: Canvas;
+    type Error;
+
+    // an empty frame buffer for this panel
+    fn display(&self) -> Self::Display;
+
+    // sets the panel up, the OLED needs it before the first update and e-paper after `sleep`
+    fn init(&mut self) -> Result<(), Self::Error>;
+
+    // sends the rows from `top` to `bottom` of the frame buffer to the panel
+    fn update(&mut self, display: &Self::Display, top: u32, bottom: u32)
+        -> Result<(), Self::Error>;
+
+    // shows what was sent, a partial refresh may leave ghosting behind on e-paper
+    fn refresh(&mut self, refresh: Refresh) -> Result<(), Self::Error>;
+
+    fn sleep(&mut self) -> Result<(), Self::Error>;
+}
+
+// the rows of the frame buffer are sent as they are, a partial window spans whole rows
+fn update_epd<EPD, SPI, CS, BUSY, DC, RST>(
+    epd: &mut EPD,
+    spi: &mut SPI,
+    buffer: &[u8],
+    top: u32,
+    bottom: u32,
+) -> Result<(), SPI::Error>
+where
+    EPD: WaveshareDisplay<SPI, CS, BUSY, DC, RST>,
+    SPI: Write<u8>,
+    CS: OutputPin,
+    BUSY: InputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+{
+    let (width, height) = (epd.width(), epd.height());
+    let bytes_per_row = width as usize / 8;
+    let bottom = bottom.min(height - 1);
+
+    if top == 0 && bottom == height - 1 {
+        return epd.update_frame(spi, buffer);
+    }
+
+    let rows = &buffer[top as usize * bytes_per_row..(bottom as usize + 1) * bytes_per_row];
+    epd.update_partial_frame(spi, rows, 0, top, width, bottom - top + 1)
+}
+
+fn refresh_epd<EPD, SPI, CS, BUSY, DC, RST>(
+    epd: &mut EPD,
+    spi: &mut SPI,
+    refresh: Refresh,
+) -> Result<(), SPI::Error>
+where
+    EPD: WaveshareDisplay<SPI, CS, BUSY, DC, RST>,
+    SPI: Write<u8>,
+    CS: OutputPin,
+    BUSY: InputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+{
+    let lut = match refresh {
+        Refresh::Full => RefreshLUT::FULL,
+        Refresh::Partial => RefreshLUT::QUICK,
+    };
+    epd.set_lut(spi, Some(lut))?;
+
+    epd.display_frame(spi)
+}
+
+// Waveshare 4.2" e-paper, 400x300
+pub struct Epd4in2Panel<SPI, CS, BUSY, DC, RST, DELAY> {
+    epd: EPD4in2<SPI, CS, BUSY, DC, RST>,
+    spi: SPI,
+    delay: DELAY,
+}
+
+impl<SPI, CS, BUSY, DC, RST, DELAY> Epd4in2Panel<SPI, CS, BUSY, DC, RST, DELAY>
+where
+    SPI: Write<u8>,
+    CS: OutputPin,
+    BUSY: InputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayMs<u8>,
+{
+    pub fn new(
+        mut spi: SPI,
+        cs: CS,
+        busy: BUSY,
+        dc: DC,
+        rst: RST,
+        mut delay: DELAY,
+    ) -> Result<Self, SPI::Error> {
+        let epd = EPD4in2::new(&mut spi, cs, busy, dc, rst, &mut delay)?;
+
+        Ok(Epd4in2Panel { epd, spi, delay })
+    }
+}
+
+impl<SPI, CS, BUSY, DC, RST, DELAY> Panel for Epd4in2Panel<SPI, CS, BUSY, DC, RST, DELAY>
+where
+    SPI: Write<u8>,
+    CS: OutputPin,
+    BUSY: InputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayMs<u8>,
+{
+    type Display = Display4in2;
+    type Error = SPI::Error;
+
+    fn display(&self) -> Display4in2 {
+        Display4in2::default()
+    }
+
+    fn init(&mut self) -> Result<(), Self::Error> {
+        self.epd.wake_up(&mut self.spi, &mut self.delay)
+    }
+
+    fn update(&mut self, display: &Display4in2, top: u32, bottom: u32) -> Result<(), Self::Error> {
+        update_epd(&mut self.epd, &mut self.spi, display.buffer(), top, bottom)
+    }
+
+    fn refresh(&mut self, refresh: Refresh) -> Result<(), Self::Error> {
+        refresh_epd(&mut self.epd, &mut self.spi, refresh)
+    }
+
+    fn sleep(&mut self) -> Result<(), Self::Error> {
+        self.epd.sleep(&mut self.spi)
+    }
+}
+
+// Waveshare 2.9" e-paper, 128x296 upright
+pub struct Epd2in9Panel<SPI, CS, BUSY, DC, RST, DELAY> {
+    epd: EPD2in9<SPI, CS, BUSY, DC, RST>,
+    spi: SPI,
+    delay: DELAY,
+}
+
+impl<SPI, CS, BUSY, DC, RST, DELAY> Epd2in9Panel<SPI, CS, BUSY, DC, RST, DELAY>
+where
+    SPI: Write<u8>,
+    CS: OutputPin,
+    BUSY: InputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayMs<u8>,
+{
+    pub fn new(
+        mut spi: SPI,
+        cs: CS,
+        busy: BUSY,
+        dc: DC,
+        rst: RST,
+        mut delay: DELAY,
+    ) -> Result<Self, SPI::Error> {
+        let epd = EPD2in9::new(&mut spi, cs, busy, dc, rst, &mut delay)?;
+
+        Ok(Epd2in9Panel { epd, spi, delay })
+    }
+}
+
+impl<SPI, CS, BUSY, DC, RST, DELAY> Panel for Epd2in9Panel<SPI, CS, BUSY, DC, RST, DELAY>
+where
+    SPI: Write<u8>,
+    CS: OutputPin,
+    BUSY: InputPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayMs<u8>,
+{
+    type Display = Display2in9;
+    type Error = SPI::Error;
+
+    // the driver reports the unrotated size to embedded-graphics, so the panel stays upright
+    fn display(&self) -> Display2in9 {
+        Display2in9::default()
+    }
+
+    fn init(&mut self) -> Result<(), Self::Error> {
+        self.epd.wake_up(&mut self.spi, &mut self.delay)
+    }
+
+    fn update(&mut self, display: &Display2in9, top: u32, bottom: u32) -> Result<(), Self::Error> {
+        update_epd(&mut self.epd, &mut self.spi, display.buffer(), top, bottom)
+    }
+
+    fn refresh(&mut self, refresh: Refresh) -> Result<(), Self::Error> {
+        refresh_epd(&mut self.epd, &mut self.spi, refresh)
+    }
+
+    fn sleep(&mut self) -> Result<(), Self::Error> {
+        self.epd.sleep(&mut self.spi)
+    }
+}
+
+// SSD1306 128x64 OLED, there is no ghosting so both refreshes are the same
+impl<T, E> Panel for SSD1306<T>
+where
+    T: i2c::Write<Error = E>,
+{
+    type Display = Display128x64;
+    type Error = E;
+
+    fn display(&self) -> Display128x64 {
+        Display128x64::default()
+    }
+
+    fn init(&mut self) -> Result<(), E> {
+        self.power_on()
+    }
+
+    fn update(&mut self, display: &Display128x64, top: u32, bottom: u32) -> Result<(), E> {
+        let bottom = bottom.min(ssd1306::HEIGHT - 1);
+        self.update_pages(display, top / 8, bottom / 8)
+    }
+
+    fn refresh(&mut self, _refresh: Refresh) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn sleep(&mut self) -> Result<(), E> {
+        SSD1306::sleep(self)
+    }
+}
@@ -0,0 +1,69 @@
+use embedded_graphics::prelude::*;
+
+use super::panel::Panel;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Refresh {
+    Full,
+    Partial,
+}
+
+// partial updates use the quick LUT and leave ghosting behind, so every `full_every`
+// updates the whole panel is refreshed with the full LUT
+pub struct RefreshPolicy {
+    full_every: u16,
+    partial_updates: u16,
+    full_pending: bool,
+}
+
+impl RefreshPolicy {
+    pub fn init(full_every: u16) -> RefreshPolicy {
+        RefreshPolicy {
+            full_every,
+            partial_updates: 0,
+            full_pending: false,
+        }
+    }
+
+    // e.g. after redrawing the titles
+    pub fn force_full(&mut self) {
+        self.full_pending = true;
+    }
+
+    pub fn next_refresh(&mut self) -> Refresh {
+        if self.full_pending || self.partial_updates >= self.full_every {
+            self.full_pending = false;
+            self.partial_updates = 0;
+            Refresh::Full
+        } else {
+            self.partial_updates += 1;
+            Refresh::Partial
+        }
+    }
+
+    // shows the region between `top_left` and `bottom_right`, the same corners as for
+    // `clear_numbers`; a full refresh sends the whole frame buffer
+    pub fn refresh<P: Panel>(
+        &mut self,
+        panel: &mut P,
+        display: &P::Display,
+        top_left: (i32, i32),
+        bottom_right: (i32, i32),
+    ) -> Result<Refresh, P::Error> {
+        let refresh = self.next_refresh();
+        let last_row = display.size().height as i32 - 1;
+
+        let (top, bottom) = match refresh {
+            Refresh::Full => (0, last_row),
+            Refresh::Partial => {
+                let top = top_left.1.clamp(0, last_row);
+                (top, bottom_right.1.clamp(top, last_row))
+            }
+        };
+
+        panel.update(display, top as u32, bottom as u32)?;
+        panel.refresh(refresh)?;
+
+        Ok(refresh)
+    }
+}
@@ -0,0 +1,859 @@
+use arrayvec::{ArrayString, ArrayVec};
+use core::fmt::{self, Write};
+use embedded_graphics::{pixelcolor::BinaryColor, prelude::*};
+
+use super::{
+    chart::draw_chart,
+    clear_numbers,
+    digits::{self, draw_digits},
+    draw_large_text, draw_mid_text, draw_small_text,
+    format::{fixed, Quantity},
+    icons::{draw_arrow, draw_icon, Icon, ARROW_SIZE, ICON_SIZE},
+    Canvas,
+};
+use crate::{
+    alert::{escalation::AlertLevel, profile::Thresholds},
+    history::History,
+    number_representations::Unit,
+    statistics::{ReadingStatistics, RollingStatistics, Trend},
+    ventilation::Advice,
+};
+
+const MAX_FIELDS: usize = 10;
+const ICON_GAP: i32 = 8;
+// the layouts are made for the 4.2" panel, anything smaller gets the compact one
+const FULL_SIZE: (u32, u32) = (400, 300);
+
+type FieldText = ArrayString<[u8; 32]>;
+
+// how the live page shows the readings
+#[derive(Clone, Copy, PartialEq)]
+pub enum Layout {
+    Details,
+    // the CO2 value in digits that can be read across the room
+    BigDigits,
+    // picked for small panels, e.g. the OLED, and never stored in the settings
+    Compact,
+}
+
+pub const LAYOUTS: [Layout; 2] = [Layout::Details, Layout::BigDigits];
+
+impl Layout {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Layout::Details => "Details",
+            Layout::BigDigits => "Big CO2",
+            Layout::Compact => "Compact",
+        }
+    }
+
+    pub fn index(&self) -> u16 {
+        match self {
+            Layout::Details | Layout::Compact => 0,
+            Layout::BigDigits => 1,
+        }
+    }
+
+    // unknown indices fall back to the default layout
+    pub fn from_index(index: u16) -> Layout {
+        match index {
+            1 => Layout::BigDigits,
+            _ => Layout::Details,
+        }
+    }
+}
+
+// the first of the layouts in the menu
+impl Default for Layout {
+    fn default() -> Self {
+        LAYOUTS[0]
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Page {
+    Live,
+    History,
+    Statistics,
+    Diagnostics,
+    Settings,
+}
+
+impl Page {
+    pub fn next(&self) -> Page {
+        match self {
+            Page::Live => Page::History,
+            Page::History => Page::Statistics,
+            Page::Statistics => Page::Diagnostics,
+            Page::Diagnostics => Page::Settings,
+            Page::Settings => Page::Live,
+        }
+    }
+
+    // small panels only have room for the readings and the chart
+    fn next_compact(&self) -> Page {
+        match self {
+            Page::Live => Page::History,
+            _ => Page::Live,
+        }
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            Page::Live => "Air Quality",
+            Page::History => "CO2 History",
+            Page::Statistics => "Statistics",
+            Page::Diagnostics => "Sensor",
+            Page::Settings => "Settings",
+        }
+    }
+
+    fn labels(&self, layout: Layout) -> &'static [(&'static str, (i32, i32))] {
+        match self {
+            _ if layout == Layout::Compact => &[],
+            Page::Live if layout == Layout::BigDigits => &[("ppm", (320, 162))],
+            Page::Live => &[
+                ("Carbon Dioxide:", (20, 90)),
+                ("Temperature:", (20, 130)),
+                ("Humidity:", (20, 170)),
+            ],
+            Page::History => &[],
+            Page::Statistics => &[
+                ("8h TWA:", (20, 65)),
+                ("15min STEL:", (20, 95)),
+                // lined up with the columns of the rows below
+                ("  min   max   avg", (110, 140)),
+                ("CO2", (20, 170)),
+                ("Temp", (20, 205)),
+                ("Hum.", (20, 240)),
+            ],
+            Page::Diagnostics => &[
+                ("Firmware:", (20, 90)),
+                ("Interval:", (20, 130)),
+                ("Temp offset:", (20, 170)),
+                ("Profile:", (20, 210)),
+                ("Uptime:", (20, 250)),
+            ],
+            Page::Settings => &[
+                ("Hold buttons 1 + 2", (20, 90)),
+                ("to open the menu", (20, 130)),
+            ],
+        }
+    }
+
+    // top left corner of the status icons
+    fn icons(&self, layout: Layout) -> Option<(i32, i32)> {
+        match self {
+            _ if layout == Layout::Compact => None,
+            Page::Live if layout == Layout::BigDigits => Some((300, 190)),
+            Page::Live => Some((20, 215)),
+            _ => None,
+        }
+    }
+
+    // top left corner and size
+    fn chart(&self, layout: Layout, size: Size) -> Option<((i32, i32), (i32, i32))> {
+        match self {
+            Page::History if layout == Layout::Compact => {
+                Some(((0, 0), (size.width as i32, size.height as i32)))
+            }
+            _ if layout == Layout::Compact => None,
+            Page::Live => Some(((290, 0), (110, 80))),
+            Page::History => Some(((10, 60), (380, 230))),
+            _ => None,
+        }
+    }
+}
+
+// everything the pages show, collected by the main loop on each refresh, temperatures in °C
+pub struct ScreenData<'a> {
+    pub co2: f32,
+    pub temperature: f32,
+    pub temperature_unit: Unit,
+    pub humidity: f32,
+    pub twa: f32,
+    pub stel: f32,
+    pub advice: Advice,
+    pub alert_level: AlertLevel,
+    // the alarm is held back by the quiet hours
+    pub silenced: bool,
+    // no reading for a while, the values shown are stale
+    pub sensor_error: bool,
+    pub history: &'a History,
+    pub statistics: &'a ReadingStatistics,
+    pub thresholds: Thresholds,
+    pub profile: &'static str,
+    pub firmware_version: [u8; 2],
+    pub measurement_interval: u16,
+    // in 0.01 °C, as the SCD30 reports it
+    pub temperature_offset: u16,
+    pub uptime_ms: u64,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Style {
+    Text,
+    Large,
+    Small,
+    Digits,
+    // trend symbols drawn as arrows
+    Arrow,
+}
+
+impl Style {
+    fn char_size(&self) -> (i32, i32) {
+        match self {
+            Style::Text => (12, 16),
+            Style::Large => (24, 32),
+            Style::Small => (6, 8),
+            Style::Digits => (digits::ADVANCE, digits::DIGIT_HEIGHT),
+            Style::Arrow => (ARROW_SIZE, ARROW_SIZE),
+        }
+    }
+}
+
+struct Field {
+    position: (i32, i32),
+    chars: i32,
+    style: Style,
+    text: FieldText,
+}
+
+impl Field {
+    fn new(position: (i32, i32), chars: i32, arguments: fmt::Arguments) -> Field {
+        let mut text = FieldText::new();
+        // text that doesn't fit is cut off
+        text.write_fmt(arguments).ok();
+
+        Field {
+            position,
+            chars,
+            style: Style::Text,
+            text,
+        }
+    }
+
+    fn with_style(mut self, style: Style) -> Field {
+        self.style = style;
+        self
+    }
+
+    fn bottom_right(&self) -> (i32, i32) {
+        let (width, height) = self.style.char_size();
+        (
+            self.position.0 + self.chars * width - 1,
+            self.position.1 + height - 1,
+        )
+    }
+
+    fn draw<D: Canvas>(&self, mut display: D) -> D {
+        match self.style {
+            Style::Text => draw_mid_text(&mut display, &self.text, self.position),
+            Style::Large => draw_large_text(&mut display, &self.text, self.position),
+            Style::Small => draw_small_text(&mut display, &self.text, self.position),
+            Style::Digits => display = draw_digits(&self.text, self.position, display),
+            Style::Arrow => {
+                for (index, symbol) in self.text.chars().enumerate() {
+                    let x = self.position.0 + index as i32 * ARROW_SIZE;
+                    display = draw_arrow(symbol, (x, self.position.1), display);
+                }
+            }
+        }
+
+        display
+    }
+}
+
+fn advice(advice: Advice) -> Field {
+    match advice {
+        Advice::Steady => Field::new((20, 280), 31, format_args!("")),
+        Advice::Ventilate(minutes) => {
+            Field::new((20, 280), 31, format_args!("Ventilate ~{} min", minutes))
+        }
+        Advice::LimitIn(minutes) => Field::new(
+            (20, 280),
+            31,
+            format_args!("Limit reached in ~{} min", minutes),
+        ),
+    }
+}
+
+// today's minimum, maximum and average
+fn summary(rolling: &RollingStatistics) -> [f32; 3] {
+    [rolling.min(), rolling.max(), rolling.average()]
+}
+
+fn statistics_row(
+    y: i32,
+    trend: Trend,
+    summary: [f32; 3],
+    quantity: Quantity,
+    symbol: &str,
+) -> [Field; 2] {
+    [
+        Field::new((80, y), 1, format_args!("{}", trend.symbol())).with_style(Style::Arrow),
+        Field::new(
+            (110, y),
+            23,
+            format_args!(
+                "{:5} {:5} {:5} {}",
+                fixed(summary[0], quantity),
+                fixed(summary[1], quantity),
+                fixed(summary[2], quantity),
+                symbol
+            ),
+        ),
+    ]
+}
+
+fn fields(page: Page, layout: Layout, data: &ScreenData) -> ArrayVec<[Field; MAX_FIELDS]> {
+    let mut fields = ArrayVec::new();
+    let unit = data.temperature_unit;
+
+    match page {
+        Page::Live if layout == Layout::Compact => {
+            fields.push(
+                Field::new(
+                    (0, 0),
+                    5,
+                    format_args!("{}", fixed(data.co2, Quantity::Co2)),
+                )
+                .with_style(Style::Large),
+            );
+            fields.push(
+                Field::new(
+                    (0, 36),
+                    21,
+                    format_args!("ppm {}", data.thresholds.air_quality(data.co2).name()),
+                )
+                .with_style(Style::Small),
+            );
+            fields.push(
+                Field::new(
+                    (0, 46),
+                    21,
+                    format_args!(
+                        "{} {}  {} %",
+                        fixed(
+                            unit.convert_temperature(&data.temperature),
+                            Quantity::Temperature
+                        ),
+                        unit.symbol(),
+                        fixed(data.humidity, Quantity::Humidity)
+                    ),
+                )
+                .with_style(Style::Small),
+            );
+            let advice = match data.advice {
+                Advice::Steady => Field::new((0, 56), 21, format_args!("")),
+                Advice::Ventilate(minutes) => {
+                    Field::new((0, 56), 21, format_args!("Ventilate ~{} min", minutes))
+                }
+                Advice::LimitIn(minutes) => {
+                    Field::new((0, 56), 21, format_args!("Limit in ~{} min", minutes))
+                }
+            };
+            fields.push(advice.with_style(Style::Small));
+        }
+        Page::Live if layout == Layout::BigDigits => {
+            fields.push(
+                Field::new(
+                    (20, 90),
+                    5,
+                    format_args!("{}", fixed(data.co2, Quantity::Co2)),
+                )
+                .with_style(Style::Digits),
+            );
+            fields.push(
+                Field::new(
+                    (20, 190),
+                    8,
+                    format_args!("{}", data.thresholds.air_quality(data.co2).name()),
+                )
+                .with_style(Style::Large),
+            );
+            fields.push(Field::new(
+                (20, 240),
+                20,
+                format_args!(
+                    "{} {}   {} %",
+                    fixed(
+                        unit.convert_temperature(&data.temperature),
+                        Quantity::Temperature
+                    ),
+                    unit.symbol(),
+                    fixed(data.humidity, Quantity::Humidity)
+                ),
+            ));
+            fields.push(advice(data.advice));
+        }
+        Page::Live => {
+            fields.push(Field::new(
+                (220, 90),
+                14,
+                format_args!("{} ppm", fixed(data.co2, Quantity::Co2)),
+            ));
+            fields.push(Field::new(
+                (220, 130),
+                14,
+                format_args!(
+                    "{} {}",
+                    fixed(
+                        unit.convert_temperature(&data.temperature),
+                        Quantity::Temperature
+                    ),
+                    unit.symbol()
+                ),
+            ));
+            fields.push(Field::new(
+                (220, 170),
+                14,
+                format_args!("{} %", fixed(data.humidity, Quantity::Humidity)),
+            ));
+            fields.push(advice(data.advice));
+        }
+        Page::History => {}
+        Page::Statistics => {
+            fields.push(Field::new(
+                (220, 65),
+                14,
+                format_args!("{} ppm", fixed(data.twa, Quantity::Co2)),
+            ));
+            fields.push(Field::new(
+                (220, 95),
+                14,
+                format_args!("{} ppm", fixed(data.stel, Quantity::Co2)),
+            ));
+
+            let statistics = data.statistics;
+            let temperature = summary(&statistics.temperature)
+                .map(|temperature| unit.convert_temperature(&temperature));
+
+            fields.extend(statistics_row(
+                170,
+                statistics.co2.trend(),
+                summary(&statistics.co2),
+                Quantity::Co2,
+                "ppm",
+            ));
+            fields.extend(statistics_row(
+                205,
+                statistics.temperature.trend(),
+                temperature,
+                Quantity::Temperature,
+                unit.symbol(),
+            ));
+            fields.extend(statistics_row(
+                240,
+                statistics.humidity.trend(),
+                summary(&statistics.humidity),
+                Quantity::Humidity,
+                "%",
+            ));
+        }
+        Page::Diagnostics => {
+            let version = data.firmware_version;
+            fields.push(Field::new(
+                (220, 90),
+                14,
+                format_args!("{}.{}", version[0], version[1]),
+            ));
+            fields.push(Field::new(
+                (220, 130),
+                14,
+                format_args!("{} s", data.measurement_interval),
+            ));
+            fields.push(Field::new(
+                (220, 170),
+                14,
+                format_args!(
+                    "{} {}",
+                    fixed(
+                        unit.convert_difference(&(data.temperature_offset as f32 / 100.0)),
+                        Quantity::Temperature
+                    ),
+                    unit.symbol()
+                ),
+            ));
+            fields.push(Field::new((220, 210), 14, format_args!("{}", data.profile)));
+            fields.push(Field::new(
+                (220, 250),
+                14,
+                format_args!("{} min", data.uptime_ms / 60_000),
+            ));
+        }
+        Page::Settings => {}
+    }
+
+    fields
+}
+
+// the face for the CO2 level and a hint about the device state
+fn status_icons(data: &ScreenData) -> [Option<Icon>; 2] {
+    if data.sensor_error {
+        return [Some(Icon::SensorError), None];
+    }
+
+    let level = data.alert_level;
+    let hint = if data.silenced && level == AlertLevel::Limit {
+        Some(Icon::Snooze)
+    } else if level == AlertLevel::High
+        || level == AlertLevel::Limit
+        || data.advice != Advice::Steady
+    {
+        Some(Icon::OpenWindow)
+    } else {
+        None
+    };
+
+    [Some(Icon::for_alert_level(level)), hint]
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Redraw {
+    Nothing,
+    // a new page, the whole panel has to be refreshed
+    Full,
+    // top left and bottom right corner of everything that changed
+    Region((i32, i32), (i32, i32)),
+}
+
+impl Redraw {
+    fn include(self, top_left: (i32, i32), bottom_right: (i32, i32)) -> Redraw {
+        match self {
+            Redraw::Nothing => Redraw::Region(top_left, bottom_right),
+            Redraw::Full => Redraw::Full,
+            Redraw::Region(from, to) => Redraw::Region(
+                (from.0.min(top_left.0), from.1.min(top_left.1)),
+                (to.0.max(bottom_right.0), to.1.max(bottom_right.1)),
+            ),
+        }
+    }
+}
+
+// remembers what each page region shows so a render only redraws what changed
+pub struct ScreenManager {
+    page: Page,
+    layout: Layout,
+    // the panel is smaller than the layouts
+    compact: bool,
+    drawn: ArrayVec<[FieldText; MAX_FIELDS]>,
+    chart: Option<(u32, Thresholds)>,
+    icons: Option<[Option<Icon>; 2]>,
+    full_redraw: bool,
+}
+
+impl ScreenManager {
+    pub fn init() -> ScreenManager {
+        ScreenManager {
+            page: Page::Live,
+            layout: Layout::default(),
+            compact: false,
+            drawn: ArrayVec::new(),
+            chart: None,
+            icons: None,
+            full_redraw: true,
+        }
+    }
+
+    pub fn page(&self) -> Page {
+        self.page
+    }
+
+    pub fn show(&mut self, page: Page) {
+        if page != self.page {
+            self.page = page;
+            self.full_redraw = true;
+        }
+    }
+
+    pub fn layout(&self) -> Layout {
+        self.layout
+    }
+
+    pub fn set_layout(&mut self, layout: Layout) {
+        if layout != self.layout {
+            self.layout = layout;
+            self.full_redraw = true;
+        }
+    }
+
+    // draws the whole page again on the next render, e.g. after the settings menu
+    pub fn invalidate(&mut self) {
+        self.full_redraw = true;
+    }
+
+    pub fn next_page(&mut self) {
+        if self.compact {
+            self.show(self.page.next_compact());
+        } else {
+            self.show(self.page.next());
+        }
+    }
+
+    // the layout the live page is drawn with on the current panel
+    fn current_layout(&self) -> Layout {
+        if self.compact {
+            Layout::Compact
+        } else {
+            self.layout
+        }
+    }
+
+    pub fn render<D: Canvas>(&mut self, mut display: D, data: &ScreenData) -> (D, Redraw) {
+        let mut redraw = Redraw::Nothing;
+        let size = display.size();
+
+        let compact = size.width < FULL_SIZE.0 || size.height < FULL_SIZE.1;
+        if compact != self.compact {
+            self.compact = compact;
+            self.full_redraw = true;
+            if compact && !matches!(self.page, Page::Live | Page::History) {
+                // only the live page and the chart fit
+                self.page = Page::Live;
+            }
+        }
+        let layout = self.current_layout();
+
+        if self.full_redraw {
+            self.full_redraw = false;
+            self.drawn.clear();
+            self.chart = None;
+            self.icons = None;
+            redraw = Redraw::Full;
+
+            display.clear(BinaryColor::Off).unwrap();
+            if !compact {
+                draw_large_text(&mut display, self.page.title(), (20, 10));
+            }
+            for (label, position) in self.page.labels(layout).iter() {
+                draw_mid_text(&mut display, label, *position);
+            }
+        }
+
+        for (index, field) in fields(self.page, layout, data).iter().enumerate() {
+            if self.drawn.get(index) == Some(&field.text) {
+                continue;
+            }
+
+            display = clear_numbers(display, field.position, field.bottom_right());
+            display = field.draw(display);
+            redraw = redraw.include(field.position, field.bottom_right());
+
+            match self.drawn.get_mut(index) {
+                Some(drawn) => *drawn = field.text,
+                None => self.drawn.push(field.text),
+            }
+        }
+
+        if let Some(top_left) = self.page.icons(layout) {
+            let icons = status_icons(data);
+
+            if self.icons != Some(icons) {
+                self.icons = Some(icons);
+                let bottom_right = (
+                    top_left.0 + 2 * ICON_SIZE + ICON_GAP - 1,
+                    top_left.1 + ICON_SIZE - 1,
+                );
+
+                display = clear_numbers(display, top_left, bottom_right);
+                for (index, icon) in icons.iter().enumerate() {
+                    if let Some(icon) = icon {
+                        let x = top_left.0 + index as i32 * (ICON_SIZE + ICON_GAP);
+                        display = draw_icon(*icon, (x, top_left.1), display);
+                    }
+                }
+                redraw = redraw.include(top_left, bottom_right);
+            }
+        }
+
+        if let Some((top_left, size)) = self.page.chart(layout, size) {
+            let chart = Some((data.history.revision(), data.thresholds));
+
+            if self.chart != chart {
+                self.chart = chart;
+                let bottom_right = (top_left.0 + size.0 - 1, top_left.1 + size.1 - 1);
+
+                display = clear_numbers(display, top_left, bottom_right);
+                display = draw_chart(data.history, &data.thresholds, top_left, size, display);
+                redraw = redraw.include(top_left, bottom_right);
+            }
+        }
+
+        (display, redraw)
+    }
+}
+
+impl Default for ScreenManager {
+    fn default() -> Self {
+        ScreenManager::init()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use epd_waveshare::epd4in2::Display4in2;
+
+    use super::*;
+    use crate::alert::profile::Profile;
+
+    // of the text fields, see `Style::Text`
+    const CHAR_WIDTH: i32 = 12;
+
+    fn data<'a>(history: &'a History, statistics: &'a ReadingStatistics) -> ScreenData<'a> {
+        ScreenData {
+            co2: 612.0,
+            temperature: 21.5,
+            temperature_unit: Unit::Celsius,
+            humidity: 40.0,
+            twa: 550.0,
+            stel: 600.0,
+            advice: Advice::Steady,
+            alert_level: AlertLevel::Good,
+            silenced: false,
+            sensor_error: false,
+            history,
+            statistics,
+            thresholds: Profile::Standard.thresholds(),
+            profile: Profile::Standard.name(),
+            firmware_version: [3, 66],
+            measurement_interval: 2,
+            temperature_offset: 0,
+            uptime_ms: 0,
+        }
+    }
+
+    fn render(screens: &mut ScreenManager, data: &ScreenData) -> Redraw {
+        screens.render(Display4in2::default(), data).1
+    }
+
+    #[test]
+    fn pages_cycle_back_to_live() {
+        let mut screens = ScreenManager::init();
+        let mut titles = ArrayVec::<[&str; 5]>::new();
+
+        for _ in 0..5 {
+            titles.push(screens.page().title());
+            screens.next_page();
+        }
+
+        assert!(screens.page() == Page::Live);
+        assert!(!titles[1..].contains(&titles[0]));
+    }
+
+    #[test]
+    fn first_render_and_new_pages_are_full() {
+        let history = History::init(60_000);
+        let statistics = ReadingStatistics::init();
+        let mut screens = ScreenManager::init();
+
+        assert!(render(&mut screens, &data(&history, &statistics)) == Redraw::Full);
+        assert!(render(&mut screens, &data(&history, &statistics)) == Redraw::Nothing);
+
+        screens.next_page();
+        assert!(render(&mut screens, &data(&history, &statistics)) == Redraw::Full);
+
+        // showing the same page again changes nothing
+        screens.show(Page::History);
+        assert!(render(&mut screens, &data(&history, &statistics)) == Redraw::Nothing);
+    }
+
+    #[test]
+    fn only_changed_fields_are_redrawn() {
+        let history = History::init(60_000);
+        let statistics = ReadingStatistics::init();
+        let mut screens = ScreenManager::init();
+        render(&mut screens, &data(&history, &statistics));
+
+        let mut changed = data(&history, &statistics);
+        changed.co2 = 640.0;
+        let co2_field = Redraw::Region((220, 90), (220 + 14 * CHAR_WIDTH - 1, 105));
+        assert!(render(&mut screens, &changed) == co2_field);
+
+        // the region spans both fields
+        changed.co2 = 700.0;
+        changed.humidity = 45.0;
+        let both = Redraw::Region((220, 90), (220 + 14 * CHAR_WIDTH - 1, 185));
+        assert!(render(&mut screens, &changed) == both);
+
+        // values that round to the same text are left alone
+        changed.humidity = 45.001;
+        assert!(render(&mut screens, &changed) == Redraw::Nothing);
+    }
+
+    #[test]
+    fn switching_the_unit_redraws_the_temperature() {
+        let history = History::init(60_000);
+        let statistics = ReadingStatistics::init();
+        let mut screens = ScreenManager::init();
+        render(&mut screens, &data(&history, &statistics));
+
+        let mut fahrenheit = data(&history, &statistics);
+        fahrenheit.temperature_unit = Unit::Fahrenheit;
+        let temperature_field = Redraw::Region((220, 130), (220 + 14 * CHAR_WIDTH - 1, 145));
+        assert!(render(&mut screens, &fahrenheit) == temperature_field);
+
+        let temperature = &fields(Page::Live, Layout::Details, &fahrenheit)[1];
+        assert_eq!(temperature.text.as_str(), " 70.7 °F");
+    }
+
+    #[test]
+    fn icons_show_the_alert_level_and_the_device_state() {
+        let history = History::init(60_000);
+        let statistics = ReadingStatistics::init();
+        let mut data = data(&history, &statistics);
+        assert!(status_icons(&data) == [Some(Icon::Smiley), None]);
+
+        data.alert_level = AlertLevel::High;
+        assert!(status_icons(&data) == [Some(Icon::Frown), Some(Icon::OpenWindow)]);
+
+        data.alert_level = AlertLevel::Limit;
+        data.silenced = true;
+        assert!(status_icons(&data) == [Some(Icon::Warning), Some(Icon::Snooze)]);
+
+        // a sensor error hides everything else
+        data.sensor_error = true;
+        assert!(status_icons(&data) == [Some(Icon::SensorError), None]);
+    }
+
+    #[test]
+    fn new_icons_redraw_their_region() {
+        let history = History::init(60_000);
+        let statistics = ReadingStatistics::init();
+        let mut screens = ScreenManager::init();
+        render(&mut screens, &data(&history, &statistics));
+
+        let mut changed = data(&history, &statistics);
+        changed.alert_level = AlertLevel::Elevated;
+        let icons = Redraw::Region((20, 215), (20 + 2 * ICON_SIZE + ICON_GAP - 1, 246));
+        assert!(render(&mut screens, &changed) == icons);
+
+        // the big digits leave room for them next to the unit
+        screens.set_layout(Layout::BigDigits);
+        render(&mut screens, &changed);
+        changed.sensor_error = true;
+        let icons = Redraw::Region((300, 190), (300 + 2 * ICON_SIZE + ICON_GAP - 1, 221));
+        assert!(render(&mut screens, &changed) == icons);
+    }
+
+    #[test]
+    fn new_history_redraws_the_chart() {
+        let mut history = History::init(60_000);
+        let statistics = ReadingStatistics::init();
+        let mut screens = ScreenManager::init();
+        render(&mut screens, &data(&history, &statistics));
+
+        history.update(0, 600.0);
+        history.update(1_000, 700.0);
+        assert!(
+            render(&mut screens, &data(&history, &statistics))
+                == Redraw::Region((290, 0), (399, 79))
+        );
+
+        // other pages don't show it
+        screens.show(Page::Statistics);
+        render(&mut screens, &data(&history, &statistics));
+        history.update(2_000, 800.0);
+        assert!(render(&mut screens, &data(&history, &statistics)) == Redraw::Nothing);
+    }
+}
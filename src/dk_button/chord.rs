@@ -0,0 +1,126 @@
+use super::Button;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ServiceAction {
+    FactoryReset,
+    ForcedRecalibration,
+    SettingsMenu,
+    SoftReset,
+    StopMeasurement,
+}
+
+impl ServiceAction {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ServiceAction::FactoryReset => "factory reset",
+            ServiceAction::ForcedRecalibration => "forced recalibration",
+            ServiceAction::SettingsMenu => "settings menu",
+            ServiceAction::SoftReset => "soft reset",
+            ServiceAction::StopMeasurement => "stop measurement",
+        }
+    }
+}
+
+// the mask bit of a button, numbered from 1 like on the DK
+pub const fn button(number: u8) -> u8 {
+    1 << (number - 1)
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct Chord {
+    pub buttons: u8,
+    pub hold_ms: u64,
+    pub action: ServiceAction,
+}
+
+impl Chord {
+    pub const fn new(buttons: u8, hold_ms: u64, action: ServiceAction) -> Chord {
+        Chord {
+            buttons,
+            hold_ms,
+            action,
+        }
+    }
+}
+
+pub const SERVICE_CHORDS: [Chord; 5] = [
+    Chord::new(button(1) | button(2), 2000, ServiceAction::SettingsMenu),
+    Chord::new(button(2) | button(3), 3000, ServiceAction::StopMeasurement),
+    Chord::new(button(2) | button(4), 3000, ServiceAction::SoftReset),
+    Chord::new(
+        button(3) | button(4),
+        5000,
+        ServiceAction::ForcedRecalibration,
+    ),
+    Chord::new(button(1) | button(4), 3000, ServiceAction::FactoryReset),
+];
+
+pub struct ChordDetector {
+    chords: &'static [Chord],
+    held: u8,
+    held_since: u64,
+    // two or more buttons are down, stays set until all of them are released
+    engaged: bool,
+    fired: bool,
+}
+
+impl ChordDetector {
+    pub fn init(chords: &'static [Chord]) -> ChordDetector {
+        ChordDetector {
+            chords,
+            held: 0,
+            held_since: 0,
+            engaged: false,
+            fired: false,
+        }
+    }
+
+    pub fn is_engaged(&self) -> bool {
+        self.engaged
+    }
+
+    // call this after the buttons have been updated, `buttons` in DK order
+    pub fn update(&mut self, buttons: &mut [&mut Button], millis: u64) -> Option<ServiceAction> {
+        let held = buttons
+            .iter()
+            .enumerate()
+            .filter(|(_, button)| button.is_held())
+            .fold(0, |mask, (index, _)| mask | 1 << index);
+
+        if held != self.held {
+            self.held = held;
+            self.held_since = millis;
+            self.fired = false;
+        }
+
+        if held.count_ones() >= 2 {
+            self.engaged = true;
+        } else if held == 0 {
+            self.engaged = false;
+        }
+
+        if !self.engaged {
+            return None;
+        }
+
+        // the single button events of a chord must not trigger their own actions
+        for button in buttons.iter_mut() {
+            button.suppress();
+        }
+
+        let chord = self.chords.iter().find(|chord| chord.buttons == held)?;
+
+        if !self.fired && millis.saturating_sub(self.held_since) >= chord.hold_ms {
+            self.fired = true;
+            return Some(chord.action);
+        }
+
+        None
+    }
+}
+
+impl Default for ChordDetector {
+    fn default() -> Self {
+        ChordDetector::init(&SERVICE_CHORDS)
+    }
+}
@@ -0,0 +1,138 @@
+use core::cell::RefCell;
+
+use arrayvec::ArrayVec;
+use cortex_m::interrupt::{self, Mutex};
+use nrf52840_hal::{
+    gpio::{Input, Pin, PullUp},
+    gpiote::Gpiote,
+    pac::{self, GPIOTE},
+    prelude::InputPin,
+};
+
+use super::{Button, Source};
+use crate::clock::uptime;
+
+const MAX_BUTTONS: usize = 4;
+// edges per button waiting for the main loop, further edges are dropped and left to the polling
+const QUEUE_SIZE: usize = 8;
+
+struct Slot {
+    pin: Pin<Input<PullUp>>,
+    pressed: bool,
+    // the new state and the uptime in ms when it changed
+    edges: ArrayVec<[(bool, u64); QUEUE_SIZE]>,
+}
+
+impl Slot {
+    // the buttons are active low
+    fn is_pressed(&self) -> bool {
+        self.pin.is_low().unwrap()
+    }
+
+    // a port event is raised when any pin matches its sense level, so each pin senses the
+    // level it doesn't have yet
+    fn sense(&self, gpiote: &Gpiote) {
+        let port = gpiote.port();
+        let event = port.input_pin(&self.pin);
+
+        if self.pressed {
+            event.high();
+        } else {
+            event.low();
+        }
+    }
+}
+
+struct Shared {
+    gpiote: Gpiote,
+    slots: ArrayVec<[Slot; MAX_BUTTONS]>,
+}
+
+static SHARED: Mutex<RefCell<Option<Shared>>> = Mutex::new(RefCell::new(None));
+
+// queues the edges of the buttons from the GPIOTE interrupt, `Button::update` then takes
+// them instead of relying on sampling the pin at the right time; the pins move here and are
+// timestamped with the uptime, so edges that queue up while the main loop sleeps keep their
+// timing and wake it up
+pub fn listen(gpiote: GPIOTE, buttons: &mut [&mut Button]) {
+    let gpiote = Gpiote::new(gpiote);
+    let mut slots = ArrayVec::new();
+
+    for button in buttons.iter_mut().take(MAX_BUTTONS) {
+        let index = slots.len();
+
+        if let Source::Pin(pin) = core::mem::replace(&mut button.source, Source::Interrupt(index)) {
+            let mut slot = Slot {
+                pin,
+                pressed: false,
+                edges: ArrayVec::new(),
+            };
+            slot.pressed = slot.is_pressed();
+            slot.sense(&gpiote);
+            slots.push(slot);
+        }
+    }
+
+    gpiote.port().reset_events();
+    gpiote.port().enable_interrupt();
+
+    interrupt::free(|cs| {
+        SHARED.borrow(cs).replace(Some(Shared { gpiote, slots }));
+    });
+
+    unsafe { pac::NVIC::unmask(pac::Interrupt::GPIOTE) };
+}
+
+// call this from the GPIOTE interrupt handler
+pub fn on_interrupt() {
+    interrupt::free(|cs| {
+        if let Some(shared) = SHARED.borrow(cs).borrow_mut().as_mut() {
+            shared.gpiote.port().reset_events();
+            let millis = uptime::millis();
+
+            for slot in shared.slots.iter_mut() {
+                let pressed = slot.is_pressed();
+
+                if pressed != slot.pressed {
+                    slot.pressed = pressed;
+                    slot.edges.try_push((pressed, millis)).ok();
+                }
+
+                slot.sense(&shared.gpiote);
+            }
+        }
+    });
+}
+
+pub fn is_pressed(index: usize) -> bool {
+    interrupt::free(|cs| {
+        SHARED
+            .borrow(cs)
+            .borrow()
+            .as_ref()
+            .and_then(|shared| shared.slots.get(index))
+            .is_some_and(Slot::is_pressed)
+    })
+}
+
+// the oldest edge of the button and the uptime in ms when it happened
+pub fn take_edge(index: usize) -> Option<(bool, u64)> {
+    interrupt::free(|cs| {
+        SHARED
+            .borrow(cs)
+            .borrow_mut()
+            .as_mut()
+            .and_then(|shared| shared.slots.get_mut(index)?.edges.pop_at(0))
+    })
+}
+
+// the main loop doesn't go to sleep while edges are waiting
+pub fn has_edges() -> bool {
+    interrupt::free(|cs| {
+        SHARED
+            .borrow(cs)
+            .borrow()
+            .as_ref()
+            .is_some_and(|shared| shared.slots.iter().any(|slot| !slot.edges.is_empty()))
+    })
+}
@@ -3,33 +3,419 @@ use nrf52840_hal::{
     prelude::InputPin,
 };
 
+pub mod chord;
+pub mod gpiote;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ButtonEvent {
+    Pressed,
+    Released,
+    // reported once the double click window has passed without a second press
+    ShortClick,
+    LongPress,
+    DoubleClick,
+    // repeated while the button is held after a long press
+    Repeat,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct ButtonTimings {
+    pub debounce_ms: u64,
+    pub long_press_ms: u64,
+    pub double_click_ms: u64,
+    pub repeat_interval_ms: u64,
+}
+
+impl Default for ButtonTimings {
+    fn default() -> Self {
+        ButtonTimings {
+            debounce_ms: 20,
+            long_press_ms: 800,
+            double_click_ms: 300,
+            repeat_interval_ms: 200,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Clicks {
+    None,
+    // released after a short press, waiting for a possible second press
+    Waiting(u64),
+    // the second press of a double click is still held
+    Double,
+}
+
+// the debouncing and click detection of a button, fed with the raw pin level
+pub struct ButtonState {
+    timings: ButtonTimings,
+    raw: bool,
+    raw_since: u64,
+    pressed: bool,
+    pressed_at: u64,
+    changed_at: u64,
+    long_press: bool,
+    next_repeat: u64,
+    clicks: Clicks,
+    pending: Option<ButtonEvent>,
+    // set while the button is part of a chord, the clicks and long presses are swallowed
+    suppressed: bool,
+}
+
+impl ButtonState {
+    pub fn new(timings: ButtonTimings) -> ButtonState {
+        ButtonState {
+            timings,
+            raw: false,
+            raw_since: 0,
+            pressed: false,
+            pressed_at: 0,
+            changed_at: 0,
+            long_press: false,
+            next_repeat: 0,
+            clicks: Clicks::None,
+            pending: None,
+            suppressed: false,
+        }
+    }
+
+    // the debounced state
+    pub fn is_held(&self) -> bool {
+        self.pressed
+    }
+
+    // released and not waiting for a second click, nothing happens until the pin changes
+    pub fn is_idle(&self) -> bool {
+        !self.raw && !self.pressed && self.clicks == Clicks::None && self.pending.is_none()
+    }
+
+    pub fn held_for(&self, millis: u64) -> u64 {
+        if self.pressed {
+            millis.saturating_sub(self.pressed_at)
+        } else {
+            0
+        }
+    }
+
+    // drops the click in progress and any long press until the button is pressed again
+    pub fn suppress(&mut self) {
+        self.suppressed = true;
+        self.clicks = Clicks::None;
+        self.pending = None;
+    }
+
+    // `raw` is true while the pin reads pressed, returns at most one event
+    pub fn update(&mut self, raw: bool, millis: u64) -> Option<ButtonEvent> {
+        let event = self.poll(raw, millis);
+
+        match event {
+            Some(ButtonEvent::Pressed) | Some(ButtonEvent::Released) => event,
+            _ if self.suppressed => None,
+            _ => event,
+        }
+    }
+
+    fn poll(&mut self, raw: bool, millis: u64) -> Option<ButtonEvent> {
+        if let Some(event) = self.pending.take() {
+            return Some(event);
+        }
+
+        if raw != self.raw {
+            self.raw = raw;
+            self.raw_since = millis;
+        }
+
+        if self.raw != self.pressed
+            && millis.saturating_sub(self.raw_since) >= self.timings.debounce_ms
+        {
+            return Some(self.change(millis));
+        }
+
+        if self.pressed {
+            if !self.long_press
+                && millis.saturating_sub(self.pressed_at) >= self.timings.long_press_ms
+            {
+                self.long_press = true;
+                self.next_repeat = millis + self.timings.repeat_interval_ms;
+                return Some(ButtonEvent::LongPress);
+            }
+
+            if self.long_press && millis >= self.next_repeat {
+                self.next_repeat += self.timings.repeat_interval_ms;
+                return Some(ButtonEvent::Repeat);
+            }
+        } else if let Clicks::Waiting(released_at) = self.clicks {
+            if millis.saturating_sub(released_at) > self.timings.double_click_ms {
+                self.clicks = Clicks::None;
+                return Some(ButtonEvent::ShortClick);
+            }
+        }
+
+        None
+    }
+
+    // an edge seen by the interrupt counts straight away, edges within the debounce time of
+    // the last change are bounces and left to the polling to settle; `millis` is when the
+    // interrupt saw it
+    pub fn edge(&mut self, pressed: bool, millis: u64) -> Option<ButtonEvent> {
+        if pressed == self.pressed
+            || millis.saturating_sub(self.changed_at) < self.timings.debounce_ms
+        {
+            return None;
+        }
+
+        self.raw = pressed;
+        self.raw_since = millis;
+
+        Some(self.change(millis))
+    }
+
+    fn change(&mut self, millis: u64) -> ButtonEvent {
+        self.pressed = self.raw;
+        self.changed_at = millis;
+
+        if self.pressed {
+            self.on_press(millis)
+        } else {
+            self.on_release(millis);
+            ButtonEvent::Released
+        }
+    }
+
+    fn on_press(&mut self, millis: u64) -> ButtonEvent {
+        self.pressed_at = millis;
+        self.long_press = false;
+        self.suppressed = false;
+
+        if let Clicks::Waiting(_) = self.clicks {
+            self.clicks = Clicks::Double;
+            self.pending = Some(ButtonEvent::DoubleClick);
+        }
+
+        ButtonEvent::Pressed
+    }
+
+    fn on_release(&mut self, millis: u64) {
+        self.clicks = if self.long_press || self.clicks == Clicks::Double {
+            Clicks::None
+        } else {
+            Clicks::Waiting(millis)
+        };
+    }
+}
+
+// where the level of a button comes from
+enum Source {
+    Pin(Pin<Input<PullUp>>),
+    // the slot of the button in the GPIOTE edge queue, which owns the pin, see `gpiote::listen`
+    Interrupt(usize),
+}
+
 pub struct Button {
-    pin: Pin<Input<PullUp>>,
-    was_pressed: bool,
+    source: Source,
+    state: ButtonState,
 }
 
 impl Button {
     pub fn new<Mode>(pin: Pin<Mode>) -> Self {
+        Button::with_timings(pin, ButtonTimings::default())
+    }
+
+    pub fn with_timings<Mode>(pin: Pin<Mode>, timings: ButtonTimings) -> Self {
         Button {
-            pin: pin.into_pullup_input(),
-            was_pressed: false,
+            source: Source::Pin(pin.into_pullup_input()),
+            state: ButtonState::new(timings),
         }
     }
 
     pub fn is_pressed(&self) -> bool {
-        self.pin.is_low().unwrap()
+        match &self.source {
+            Source::Pin(pin) => pin.is_low().unwrap(),
+            Source::Interrupt(index) => gpiote::is_pressed(*index),
+        }
+    }
+
+    // the debounced state
+    pub fn is_held(&self) -> bool {
+        self.state.is_held()
     }
 
-    pub fn check_rising_edge(&mut self) -> bool {
-        let mut rising_edge = false;
+    pub fn held_for(&self, millis: u64) -> u64 {
+        self.state.held_for(millis)
+    }
 
-        let is_pressed = self.is_pressed();
+    pub fn is_idle(&self) -> bool {
+        self.state.is_idle()
+    }
+
+    pub fn suppress(&mut self) {
+        self.state.suppress();
+    }
 
-        if self.was_pressed && !is_pressed {
-            rising_edge = true;
+    // samples the pin and returns at most one event, call this regularly from the main loop
+    pub fn update(&mut self, millis: u64) -> Option<ButtonEvent> {
+        if let Some(event) = self.state.pending.take() {
+            return Some(event);
         }
-        self.was_pressed = is_pressed;
 
-        rising_edge
+        if let Source::Interrupt(index) = self.source {
+            // all queued edges are handled before the click timeouts, bounces are dropped
+            while let Some((pressed, at)) = gpiote::take_edge(index) {
+                if let Some(event) = self.state.edge(pressed, at) {
+                    return Some(event);
+                }
+            }
+        }
+
+        let raw = self.is_pressed();
+        self.state.update(raw, millis)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // feeds the pin level every ms from `from` until `until` and collects the events
+    fn run(state: &mut ButtonState, raw: bool, from: u64, until: u64) -> Vec<(u64, ButtonEvent)> {
+        (from..until)
+            .filter_map(|millis| state.update(raw, millis).map(|event| (millis, event)))
+            .collect()
+    }
+
+    fn events(log: &[(u64, ButtonEvent)]) -> Vec<ButtonEvent> {
+        log.iter().map(|(_, event)| *event).collect()
+    }
+
+    fn button() -> ButtonState {
+        ButtonState::new(ButtonTimings::default())
+    }
+
+    #[test]
+    fn bounces_are_ignored() {
+        let mut state = button();
+
+        let mut log = Vec::new();
+        for millis in 0..50 {
+            // the contact chatters for 15 ms before it settles
+            let raw = millis >= 15 || millis % 3 == 0;
+            log.extend(state.update(raw, millis).map(|event| (millis, event)));
+        }
+
+        assert!(events(&log) == [ButtonEvent::Pressed]);
+        assert_eq!(log[0].0, 35);
+        assert!(state.is_held());
+        assert_eq!(state.held_for(45), 10);
+    }
+
+    #[test]
+    fn short_click_waits_for_the_double_click_window() {
+        let mut state = button();
+
+        let mut log = run(&mut state, true, 0, 100);
+        log.extend(run(&mut state, false, 100, 1_000));
+
+        assert!(
+            events(&log)
+                == [
+                    ButtonEvent::Pressed,
+                    ButtonEvent::Released,
+                    ButtonEvent::ShortClick
+                ]
+        );
+        // released at 120, reported once 300 ms passed
+        assert_eq!(log[2].0, 421);
+        assert!(!state.is_held());
+    }
+
+    #[test]
+    fn second_press_is_a_double_click() {
+        let mut state = button();
+
+        let mut log = run(&mut state, true, 0, 100);
+        log.extend(run(&mut state, false, 100, 200));
+        log.extend(run(&mut state, true, 200, 300));
+        log.extend(run(&mut state, false, 300, 1_000));
+
+        assert!(
+            events(&log)
+                == [
+                    ButtonEvent::Pressed,
+                    ButtonEvent::Released,
+                    ButtonEvent::Pressed,
+                    ButtonEvent::DoubleClick,
+                    ButtonEvent::Released,
+                ]
+        );
+    }
+
+    #[test]
+    fn held_button_long_presses_and_repeats() {
+        let mut state = button();
+
+        let log = run(&mut state, true, 0, 1_500);
+        assert!(
+            events(&log)
+                == [
+                    ButtonEvent::Pressed,
+                    ButtonEvent::LongPress,
+                    ButtonEvent::Repeat,
+                    ButtonEvent::Repeat,
+                    ButtonEvent::Repeat,
+                ]
+        );
+        // pressed at 20, then 800 ms until the long press and every 200 ms after it
+        let at: Vec<u64> = log.iter().map(|(millis, _)| *millis).collect();
+        assert_eq!(at, [20, 820, 1_020, 1_220, 1_420]);
+
+        // no click after a long press
+        let log = run(&mut state, false, 1_500, 3_000);
+        assert!(events(&log) == [ButtonEvent::Released]);
+    }
+
+    #[test]
+    fn interrupt_edges_count_at_their_timestamp() {
+        let mut state = button();
+        assert!(state.is_idle());
+
+        assert!(state.edge(true, 100) == Some(ButtonEvent::Pressed));
+        // a bounce right after the press
+        assert!(state.edge(false, 105).is_none());
+        assert!(state.edge(true, 106).is_none());
+        assert!(!state.is_idle());
+
+        assert!(state.edge(false, 300) == Some(ButtonEvent::Released));
+        assert_eq!(state.held_for(300), 0);
+        // still waiting for a second click
+        assert!(!state.is_idle());
+        assert!(run(&mut state, false, 600, 700).len() == 1);
+        assert!(state.is_idle());
+    }
+
+    #[test]
+    fn suppressed_button_only_reports_press_and_release() {
+        let mut state = button();
+
+        let mut log = run(&mut state, true, 0, 100);
+        // a second button joined for a chord
+        state.suppress();
+        log.extend(run(&mut state, true, 100, 2_000));
+        log.extend(run(&mut state, false, 2_000, 3_000));
+        assert!(events(&log) == [ButtonEvent::Pressed, ButtonEvent::Released]);
+
+        // the next press counts again
+        let log = run(&mut state, true, 3_000, 4_000);
+        assert!(events(&log) == [ButtonEvent::Pressed, ButtonEvent::LongPress]);
+    }
+
+    #[test]
+    fn suppress_drops_the_click_in_progress() {
+        let mut state = button();
+
+        run(&mut state, true, 0, 100);
+        run(&mut state, false, 100, 200);
+        state.suppress();
+
+        assert!(run(&mut state, false, 200, 1_000).is_empty());
     }
 }
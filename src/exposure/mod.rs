@@ -0,0 +1,278 @@
+use crate::{clock::Weekday, scd30::SensorData};
+
+// HSE EH40 workplace exposure limits for carbon dioxide
+const DEFAULT_TWA_LIMIT: f32 = 5000.0;
+const DEFAULT_STEL_LIMIT: f32 = 15000.0;
+
+// both windows are tracked as one slot per minute
+const SLOT_MS: u64 = 60 * 1000;
+const TWA_SLOTS: usize = 8 * 60;
+const STEL_SLOTS: usize = 15;
+
+// a reading is not assumed to hold for longer than this, e.g. while measurement is stopped
+const MAX_SAMPLE_GAP_MS: u64 = 2 * 60 * 1000;
+
+pub struct ExposureTracker {
+    twa_limit: f32,
+    stel_limit: f32,
+    // the TWA starts over at midnight, i.e. when the first reading of the day came in
+    day: Option<Weekday>,
+    day_start: u64,
+    last_update: Option<(u64, f32)>,
+    // accumulated exposure in ppm * ms per minute
+    twa_slots: [f32; TWA_SLOTS],
+    // the ms of each minute backed by a reading, the TWA leaves the gaps out
+    twa_covered: [f32; TWA_SLOTS],
+    stel_slots: [f32; STEL_SLOTS],
+    current_slot: u64,
+}
+
+// both windows are ring buffers indexed by the minute
+fn index(slot: u64, slots: usize) -> usize {
+    (slot % slots as u64) as usize
+}
+
+// clears the slots the window moved past since `current_slot`
+fn clear_stale(slots: &mut [f32], current_slot: u64, slot: u64) {
+    let stale = (slot - current_slot).min(slots.len() as u64);
+    for offset in 1..=stale {
+        let index = index(current_slot + offset, slots.len());
+        slots[index] = 0.0;
+    }
+}
+
+fn average(slots: &[f32], period: f32) -> f32 {
+    if period <= 0.0 {
+        return 0.0;
+    }
+
+    let total: f32 = slots.iter().sum();
+    total / period
+}
+
+impl ExposureTracker {
+    pub fn init(twa_limit: f32, stel_limit: f32) -> ExposureTracker {
+        ExposureTracker {
+            twa_limit,
+            stel_limit,
+            day: None,
+            day_start: 0,
+            last_update: None,
+            twa_slots: [0.0; TWA_SLOTS],
+            twa_covered: [0.0; TWA_SLOTS],
+            stel_slots: [0.0; STEL_SLOTS],
+            current_slot: 0,
+        }
+    }
+
+    // `day` from the wall clock, the readings of the day before don't count for the TWA
+    pub fn update(&mut self, data: &SensorData, millis: u64, day: Weekday) {
+        if self.day.is_some() && self.day != Some(day) {
+            self.twa_slots = [0.0; TWA_SLOTS];
+            self.twa_covered = [0.0; TWA_SLOTS];
+            self.day_start = millis;
+        }
+        self.day = Some(day);
+
+        if let Some((last_millis, last_co2)) = self.last_update {
+            let mut from = last_millis;
+            let until = millis.min(last_millis + MAX_SAMPLE_GAP_MS);
+
+            // split the interval at minute boundaries so each slot gets its share
+            while from < until {
+                let slot = from / SLOT_MS;
+                let to = until.min((slot + 1) * SLOT_MS);
+                let exposure = last_co2 * (to - from) as f32;
+
+                self.advance_to(slot);
+                if from >= self.day_start {
+                    self.twa_slots[index(slot, TWA_SLOTS)] += exposure;
+                    self.twa_covered[index(slot, TWA_SLOTS)] += (to - from) as f32;
+                }
+                self.stel_slots[index(slot, STEL_SLOTS)] += exposure;
+
+                from = to;
+            }
+        }
+
+        self.advance_to(millis / SLOT_MS);
+        self.last_update = Some((millis, data.co2));
+    }
+
+    fn advance_to(&mut self, slot: u64) {
+        if slot <= self.current_slot {
+            return;
+        }
+
+        clear_stale(&mut self.twa_slots, self.current_slot, slot);
+        clear_stale(&mut self.twa_covered, self.current_slot, slot);
+        clear_stale(&mut self.stel_slots, self.current_slot, slot);
+        self.current_slot = slot;
+    }
+
+    // the average of the readings during the last 8 hours or since midnight, time without one
+    // doesn't count
+    pub fn time_weighted_average(&self) -> f32 {
+        average(&self.twa_slots, self.twa_covered.iter().sum())
+    }
+
+    pub fn short_term_exposure(&self) -> f32 {
+        let millis = self.last_update.map_or(0, |(millis, _)| millis);
+        // the current slot is still filling up, the older ones each cover a full minute; always
+        // the whole 15 minutes, a short peak right after boot is still a peak
+        let period = (STEL_SLOTS as u64 - 1) * SLOT_MS + millis % SLOT_MS;
+
+        average(&self.stel_slots, period as f32)
+    }
+
+    pub fn twa_exceeded(&self) -> bool {
+        self.time_weighted_average() > self.twa_limit
+    }
+
+    pub fn stel_exceeded(&self) -> bool {
+        self.short_term_exposure() > self.stel_limit
+    }
+
+    pub fn limit_exceeded(&self) -> bool {
+        self.twa_exceeded() || self.stel_exceeded()
+    }
+}
+
+impl Default for ExposureTracker {
+    fn default() -> Self {
+        ExposureTracker::init(DEFAULT_TWA_LIMIT, DEFAULT_STEL_LIMIT)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR_MS: u64 = 60 * SLOT_MS;
+
+    fn reading(co2: f32) -> SensorData {
+        SensorData {
+            co2,
+            temperature: 20.0,
+            humidity: 50.0,
+        }
+    }
+
+    // a reading every 2 s from `from` until `until`
+    fn feed(tracker: &mut ExposureTracker, co2: f32, from: u64, until: u64) {
+        feed_on(tracker, co2, from, until, Weekday::Monday);
+    }
+
+    fn feed_on(tracker: &mut ExposureTracker, co2: f32, from: u64, until: u64, day: Weekday) {
+        for millis in (from..until).step_by(2000) {
+            tracker.update(&reading(co2), millis, day);
+        }
+    }
+
+    fn assert_near(actual: f32, expected: f32) {
+        assert!(
+            (actual - expected).abs() < expected * 0.001 + 0.01,
+            "{} is not {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn slots_wrap_around() {
+        assert_eq!(index(0, STEL_SLOTS), 0);
+        assert_eq!(index(16, STEL_SLOTS), 1);
+        assert_eq!(index(TWA_SLOTS as u64 + 3, TWA_SLOTS), 3);
+
+        let mut slots = [1.0; 5];
+        clear_stale(&mut slots, 3, 5);
+        assert_eq!(slots, [0.0, 1.0, 1.0, 1.0, 0.0]);
+
+        // more minutes than slots clear each slot once
+        let mut slots = [1.0; 5];
+        clear_stale(&mut slots, 3, 100);
+        assert_eq!(slots, [0.0; 5]);
+    }
+
+    #[test]
+    fn constant_level_keeps_its_average() {
+        let mut tracker = ExposureTracker::default();
+
+        for hours in [1, 4, 8, 16, 23].iter() {
+            let from = tracker.last_update.map_or(0, |(millis, _)| millis + 2000);
+            feed(&mut tracker, 2000.0, from, hours * HOUR_MS);
+
+            assert_near(tracker.time_weighted_average(), 2000.0);
+            assert_near(tracker.short_term_exposure(), 2000.0);
+        }
+        assert!(!tracker.twa_exceeded());
+    }
+
+    #[test]
+    fn old_exposure_leaves_the_window() {
+        let mut tracker = ExposureTracker::default();
+
+        feed(&mut tracker, 6000.0, 0, 4 * HOUR_MS);
+        assert!(tracker.twa_exceeded());
+
+        feed(&mut tracker, 1000.0, 4 * HOUR_MS, 12 * HOUR_MS);
+        assert_near(tracker.time_weighted_average(), 1000.0);
+        assert!(!tracker.limit_exceeded());
+    }
+
+    #[test]
+    fn gaps_are_left_out_of_the_twa() {
+        let mut tracker = ExposureTracker::default();
+
+        // measurement stopped for two hours in between
+        feed(&mut tracker, 3000.0, 0, HOUR_MS);
+        feed(&mut tracker, 3000.0, 3 * HOUR_MS, 4 * HOUR_MS);
+        assert_near(tracker.time_weighted_average(), 3000.0);
+
+        // the last reading before each gap still holds for two minutes
+        feed(&mut tracker, 1000.0, 6 * HOUR_MS, 8 * HOUR_MS);
+        let expected = (3000.0 * 124.0 + 1000.0 * 120.0) / 244.0;
+        assert_near(tracker.time_weighted_average(), expected);
+    }
+
+    #[test]
+    fn twa_starts_over_at_midnight() {
+        let mut tracker = ExposureTracker::default();
+
+        feed(&mut tracker, 6000.0, 0, 2 * HOUR_MS);
+        feed_on(
+            &mut tracker,
+            800.0,
+            2 * HOUR_MS,
+            3 * HOUR_MS,
+            Weekday::Tuesday,
+        );
+
+        assert_near(tracker.time_weighted_average(), 800.0);
+        // the short term window doesn't care about the day
+        assert_near(tracker.short_term_exposure(), 800.0);
+    }
+
+    #[test]
+    fn short_peak_exceeds_the_stel() {
+        let mut tracker = ExposureTracker::default();
+
+        feed(&mut tracker, 1000.0, 0, 3 * HOUR_MS);
+        feed(
+            &mut tracker,
+            40000.0,
+            3 * HOUR_MS,
+            3 * HOUR_MS + 10 * SLOT_MS,
+        );
+        assert!(tracker.stel_exceeded());
+        assert!(!tracker.twa_exceeded());
+
+        feed(
+            &mut tracker,
+            1000.0,
+            3 * HOUR_MS + 10 * SLOT_MS,
+            3 * HOUR_MS + 30 * SLOT_MS,
+        );
+        assert_near(tracker.short_term_exposure(), 1000.0);
+    }
+}
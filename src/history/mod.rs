@@ -0,0 +1,160 @@
+use arrayvec::ArrayVec;
+
+pub const CAPACITY: usize = 96;
+
+// the CO2 averaged over fixed time buckets, the oldest bucket first; buckets without a
+// reading are NaN so the time axis stays right
+pub struct History {
+    samples: ArrayVec<[f32; CAPACITY]>,
+    bucket_ms: u64,
+    bucket_start: Option<u64>,
+    sum: f32,
+    count: u16,
+    // counts the finished buckets, tells a drawn chart that it is out of date
+    revision: u32,
+}
+
+impl History {
+    pub fn init(span_ms: u64) -> History {
+        History {
+            samples: ArrayVec::new(),
+            bucket_ms: (span_ms / CAPACITY as u64).max(1),
+            bucket_start: None,
+            sum: 0.0,
+            count: 0,
+            revision: 0,
+        }
+    }
+
+    pub fn span_ms(&self) -> u64 {
+        self.bucket_ms * CAPACITY as u64
+    }
+
+    pub fn revision(&self) -> u32 {
+        self.revision
+    }
+
+    pub fn samples(&self) -> &[f32] {
+        &self.samples
+    }
+
+    pub fn update(&mut self, millis: u64, co2: f32) {
+        let mut start = *self.bucket_start.get_or_insert(millis);
+
+        if millis.saturating_sub(start) >= self.span_ms() + self.bucket_ms {
+            // nothing of the old buckets would be left after the gap
+            self.samples.clear();
+            self.revision = self.revision.wrapping_add(1);
+            start = millis;
+            self.sum = 0.0;
+            self.count = 0;
+        }
+
+        while millis.saturating_sub(start) >= self.bucket_ms {
+            let average = if self.count > 0 {
+                self.sum / self.count as f32
+            } else {
+                f32::NAN
+            };
+            self.push(average);
+
+            start += self.bucket_ms;
+            self.sum = 0.0;
+            self.count = 0;
+        }
+
+        self.bucket_start = Some(start);
+        self.sum += co2;
+        self.count += 1;
+    }
+
+    fn push(&mut self, sample: f32) {
+        if self.samples.is_full() {
+            self.samples.remove(0);
+        }
+        self.samples.push(sample);
+        self.revision = self.revision.wrapping_add(1);
+    }
+
+    // the lowest and highest bucket
+    pub fn range(&self) -> Option<(f32, f32)> {
+        self.samples
+            .iter()
+            .filter(|sample| !sample.is_nan())
+            .fold(None, |range, &sample| match range {
+                None => Some((sample, sample)),
+                Some((low, high)) => Some((sample.min(low), sample.max(high))),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // one bucket per second
+    fn history() -> History {
+        History::init(CAPACITY as u64 * 1000)
+    }
+
+    #[test]
+    fn readings_are_averaged_per_bucket() {
+        let mut history = history();
+        assert_eq!(history.span_ms(), CAPACITY as u64 * 1000);
+
+        history.update(0, 400.0);
+        history.update(500, 600.0);
+        // the bucket is only closed by the next reading
+        assert!(history.samples().is_empty());
+
+        history.update(1000, 800.0);
+        history.update(2000, 1000.0);
+        assert_eq!(history.samples(), [500.0, 800.0]);
+        assert_eq!(history.range(), Some((500.0, 800.0)));
+    }
+
+    #[test]
+    fn empty_buckets_are_nan() {
+        let mut history = history();
+
+        history.update(0, 400.0);
+        history.update(3500, 700.0);
+        history.update(4000, 900.0);
+
+        let samples = history.samples();
+        assert_eq!(samples.len(), 4);
+        assert_eq!(samples[0], 400.0);
+        assert!(samples[1].is_nan() && samples[2].is_nan());
+        assert_eq!(samples[3], 700.0);
+        // the gaps don't count for the range
+        assert_eq!(history.range(), Some((400.0, 700.0)));
+    }
+
+    #[test]
+    fn oldest_bucket_drops_out_when_full() {
+        let mut history = history();
+
+        for second in 0..=CAPACITY as u64 + 10 {
+            history.update(second * 1000, second as f32);
+        }
+
+        let samples = history.samples();
+        assert_eq!(samples.len(), CAPACITY);
+        assert_eq!(samples[0], 10.0);
+        assert_eq!(samples[CAPACITY - 1], CAPACITY as f32 + 9.0);
+    }
+
+    #[test]
+    fn long_gap_starts_over() {
+        let mut history = history();
+
+        history.update(0, 400.0);
+        history.update(1000, 500.0);
+        history.update(1000 + history.span_ms() * 2, 900.0);
+        assert!(history.samples().is_empty());
+        assert_eq!(history.range(), None);
+
+        history.update(2000 + history.span_ms() * 2, 1000.0);
+        assert_eq!(history.samples(), [900.0]);
+    }
+}
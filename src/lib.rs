@@ -1,14 +1,18 @@
-#![no_std]
+#![cfg_attr(not(test), no_std)]
 
 use core::sync::atomic::{AtomicUsize, Ordering};
 
+// the unit tests run on the host, see `display_helper::golden`
+#[cfg(not(test))]
 use defmt_rtt as _; // global logger
 use nrf52840_hal as _; // memory layout
 
+#[cfg(not(test))]
 use panic_probe as _;
 
 // same panicking *behavior* as `panic-probe` but doesn't print a panic message
 // this prevents the panic message being printed *twice* when `defmt::panic` is invoked
+#[cfg(not(test))]
 #[defmt::panic_handler]
 fn panic() -> ! {
     cortex_m::asm::udf()
@@ -30,9 +34,17 @@ pub fn exit() -> ! {
 }
 
 pub mod alert;
+pub mod ambient_light;
 pub mod buzzer;
+pub mod clock;
 pub mod display_helper;
 pub mod dk_button;
+pub mod exposure;
+pub mod history;
 pub mod number_representations;
 pub mod rgb_led;
 pub mod scd30;
+pub mod settings;
+pub mod ssd1306;
+pub mod statistics;
+pub mod ventilation;
@@ -1,3 +1,4 @@
+#[derive(Clone, Copy, PartialEq)]
 pub enum Unit {
     Fahrenheit,
     Celsius,
@@ -12,4 +13,28 @@ impl Unit {
             Unit::Celsius => *temperature,
         }
     }
+
+    // for offsets and other differences between two temperatures in °C
+    pub fn convert_difference(&self, difference: &f32) -> f32 {
+        match self {
+            Unit::Fahrenheit => difference * 9.0 / 5.0,
+            Unit::Kelvin | Unit::Celsius => *difference,
+        }
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Unit::Fahrenheit => "°F",
+            Unit::Kelvin => "K",
+            Unit::Celsius => "°C",
+        }
+    }
+
+    pub fn next(&self) -> Unit {
+        match self {
+            Unit::Fahrenheit => Unit::Kelvin,
+            Unit::Kelvin => Unit::Celsius,
+            Unit::Celsius => Unit::Fahrenheit,
+        }
+    }
 }
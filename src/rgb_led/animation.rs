@@ -0,0 +1,254 @@
+use super::Colour;
+use crate::alert::escalation::AlertLevel;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Animation {
+    Blink {
+        colour: Colour,
+        count: u16,
+        on_ms: u32,
+        off_ms: u32,
+    },
+    // fades the base colour in and out until stopped
+    Breathe {
+        period_ms: u32,
+    },
+    // flashes the base colour once per period until stopped
+    Pulse {
+        on_ms: u32,
+        period_ms: u32,
+    },
+}
+
+impl Animation {
+    pub fn blink(count: u16) -> Animation {
+        Animation::Blink {
+            colour: Colour::WHITE,
+            count,
+            on_ms: 100,
+            off_ms: 100,
+        }
+    }
+
+    // the worse the air, the faster the LED pulses
+    pub fn for_alert_level(level: AlertLevel) -> Option<Animation> {
+        let period_ms = match level {
+            AlertLevel::Good => return None,
+            AlertLevel::Elevated => 4000,
+            AlertLevel::High => 2000,
+            AlertLevel::Limit => 500,
+        };
+
+        Some(Animation::Pulse {
+            on_ms: period_ms / 2,
+            period_ms,
+        })
+    }
+
+    // the colour to show `elapsed` ms after the start, None once the animation is over
+    fn frame(&self, elapsed: u64, base: Colour) -> Option<Colour> {
+        match *self {
+            Animation::Blink {
+                colour,
+                count,
+                on_ms,
+                off_ms,
+            } => {
+                let cycle = (on_ms + off_ms).max(1) as u64;
+                if elapsed >= cycle * count as u64 {
+                    return None;
+                }
+
+                if elapsed % cycle < on_ms as u64 {
+                    Some(colour)
+                } else {
+                    Some(base)
+                }
+            }
+            Animation::Breathe { period_ms } => {
+                let period = period_ms.max(1) as u64;
+                let phase = (elapsed % period) as f32 / period as f32;
+                let level = (1.0 - libm::cosf(2.0 * core::f32::consts::PI * phase)) / 2.0;
+
+                Some(base.scale(level))
+            }
+            Animation::Pulse { on_ms, period_ms } => {
+                let period = period_ms.max(1) as u64;
+                if elapsed % period < on_ms as u64 {
+                    Some(base)
+                } else {
+                    Some(Colour::OFF)
+                }
+            }
+        }
+    }
+}
+
+struct Running {
+    animation: Animation,
+    started: u64,
+}
+
+// a one-shot foreground animation, e.g. a blink, plays on top of a looping background one
+pub struct Animator {
+    foreground: Option<Running>,
+    background: Option<Running>,
+}
+
+impl Animator {
+    pub fn new() -> Animator {
+        Animator {
+            foreground: None,
+            background: None,
+        }
+    }
+
+    pub fn play(&mut self, animation: Animation, millis: u64) {
+        self.foreground = Some(Running {
+            animation,
+            started: millis,
+        });
+    }
+
+    pub fn set_background(&mut self, animation: Option<Animation>, millis: u64) {
+        let unchanged = match (&self.background, &animation) {
+            (Some(running), Some(animation)) => running.animation == *animation,
+            (None, None) => true,
+            _ => false,
+        };
+
+        // keep the phase of a running animation when it is set again
+        if !unchanged {
+            self.background = animation.map(|animation| Running {
+                animation,
+                started: millis,
+            });
+        }
+    }
+
+    pub fn stop(&mut self) {
+        self.foreground = None;
+        self.background = None;
+    }
+
+    pub fn is_animating(&self) -> bool {
+        self.foreground.is_some() || self.background.is_some()
+    }
+
+    pub fn frame(&mut self, millis: u64, base: Colour) -> Colour {
+        for slot in [&mut self.foreground, &mut self.background].iter_mut() {
+            if let Some(running) = slot.as_ref() {
+                match running
+                    .animation
+                    .frame(millis.saturating_sub(running.started), base)
+                {
+                    Some(colour) => return colour,
+                    None => **slot = None,
+                }
+            }
+        }
+
+        base
+    }
+}
+
+impl Default for Animator {
+    fn default() -> Self {
+        Animator::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: Colour = Colour::GREEN;
+
+    #[test]
+    fn blink_ends_after_its_count() {
+        let mut animator = Animator::new();
+        animator.play(Animation::blink(2), 1_000);
+
+        assert!(animator.frame(1_000, BASE) == Colour::WHITE);
+        assert!(animator.frame(1_150, BASE) == BASE);
+        assert!(animator.frame(1_250, BASE) == Colour::WHITE);
+        assert!(animator.is_animating());
+
+        assert!(animator.frame(1_400, BASE) == BASE);
+        assert!(!animator.is_animating());
+    }
+
+    #[test]
+    fn breathe_fades_the_base_colour() {
+        let mut animator = Animator::new();
+        animator.set_background(Some(Animation::Breathe { period_ms: 4_000 }), 0);
+
+        assert!(animator.frame(0, BASE) == Colour::OFF);
+        let half = animator.frame(1_000, BASE);
+        assert!(half.g > 120 && half.g < 135);
+        assert!(animator.frame(2_000, BASE).g >= 254);
+        // it keeps going
+        assert!(animator.frame(40_000, BASE) == Colour::OFF);
+        assert!(animator.is_animating());
+    }
+
+    #[test]
+    fn pulse_follows_the_alert_level() {
+        assert!(Animation::for_alert_level(AlertLevel::Good).is_none());
+
+        let mut animator = Animator::new();
+        animator.set_background(Animation::for_alert_level(AlertLevel::Limit), 0);
+
+        assert!(animator.frame(100, BASE) == BASE);
+        assert!(animator.frame(300, BASE) == Colour::OFF);
+        assert!(animator.frame(600, BASE) == BASE);
+    }
+
+    #[test]
+    fn setting_the_same_background_keeps_its_phase() {
+        let mut animator = Animator::new();
+        let pulse = Animation::Pulse {
+            on_ms: 500,
+            period_ms: 1_000,
+        };
+
+        animator.set_background(Some(pulse), 0);
+        animator.set_background(Some(pulse), 700);
+        assert!(animator.frame(700, BASE) == Colour::OFF);
+
+        animator.set_background(None, 800);
+        assert!(!animator.is_animating());
+        assert!(animator.frame(900, BASE) == BASE);
+    }
+
+    #[test]
+    fn foreground_plays_on_top_of_the_background() {
+        let mut animator = Animator::new();
+        animator.set_background(
+            Some(Animation::Pulse {
+                on_ms: 500,
+                period_ms: 1_000,
+            }),
+            0,
+        );
+        animator.play(
+            Animation::Blink {
+                colour: Colour::RED,
+                count: 1,
+                on_ms: 300,
+                off_ms: 0,
+            },
+            600,
+        );
+
+        // the pulse would be off here
+        assert!(animator.frame(700, BASE) == Colour::RED);
+        // back to the background once the blink is over
+        assert!(animator.frame(900, BASE) == Colour::OFF);
+        assert!(animator.frame(1_100, BASE) == BASE);
+
+        animator.stop();
+        assert!(!animator.is_animating());
+        assert!(animator.frame(1_600, BASE) == BASE);
+    }
+}
@@ -0,0 +1,77 @@
+use nrf52840_hal::{
+    gpio::{Output, Pin, PushPull},
+    prelude::*,
+};
+
+use super::{Colour, Indicator, Polarity};
+
+// channels above this count as lit when picking an LED
+const THRESHOLD: u8 = 128;
+
+// the four single-colour LEDs of the nRF52840-DK (P0.13 - P0.16, active low) stand in
+// for the colours: green on LED 1, blue on LED 2, yellow on LED 3, red on LED 4
+pub struct DkLeds {
+    leds: [Pin<Output<PushPull>>; 4],
+    polarity: Polarity,
+    colour: Colour,
+}
+
+impl DkLeds {
+    pub fn init<Mode>(
+        led_1: Pin<Mode>,
+        led_2: Pin<Mode>,
+        led_3: Pin<Mode>,
+        led_4: Pin<Mode>,
+        polarity: Polarity,
+    ) -> DkLeds {
+        DkLeds {
+            leds: [
+                led_1.into_push_pull_output(polarity.off_level()),
+                led_2.into_push_pull_output(polarity.off_level()),
+                led_3.into_push_pull_output(polarity.off_level()),
+                led_4.into_push_pull_output(polarity.off_level()),
+            ],
+            polarity,
+            colour: Colour::OFF,
+        }
+    }
+
+    fn lit(colour: &Colour) -> [bool; 4] {
+        let red = colour.r >= THRESHOLD;
+        let green = colour.g >= THRESHOLD;
+        let blue = colour.b >= THRESHOLD;
+
+        match (red, green, blue) {
+            (true, true, true) => [true; 4],
+            (true, true, false) => [false, false, true, false],
+            (true, false, _) => [false, false, false, true],
+            (false, true, _) => [true, false, false, false],
+            (false, false, true) => [false, true, false, false],
+            (false, false, false) => [false; 4],
+        }
+    }
+
+    pub fn set_colour(&mut self, colour: Colour) {
+        self.colour = colour;
+
+        for (led, lit) in self.leds.iter_mut().zip(DkLeds::lit(&colour).iter()) {
+            let high = *lit == (self.polarity == Polarity::ActiveHigh);
+            let result = if high { led.set_high() } else { led.set_low() };
+            result.unwrap();
+        }
+    }
+
+    pub fn colour(&self) -> Colour {
+        self.colour
+    }
+}
+
+impl Indicator for DkLeds {
+    fn set_colour(&mut self, colour: Colour) {
+        DkLeds::set_colour(self, colour);
+    }
+
+    fn colour(&self) -> Colour {
+        DkLeds::colour(self)
+    }
+}
@@ -1,89 +1,325 @@
 use nrf52840_hal::{
-    gpio::{Level, Output, Pin, PushPull},
-    pac::TIMER1,
-    prelude::*,
-    timer::OneShot,
-    Timer,
+    gpio::{Level, Output, Pin, Port, PushPull},
+    pac::PWM1,
 };
 
+use crate::alert::profile::Thresholds;
+
+pub mod animation;
+pub mod dk_leds;
+
+use animation::{Animation, Animator};
+
+// PWM_CLK = 16 MHz / 16 = 1 MHz, so the LED is refreshed at 1 kHz
+const COUNTERTOP: u16 = 1000;
+const GAMMA: f32 = 2.2;
+
+// the polarity bit of a duty cycle: set, the output starts high and falls at the compare value,
+// so it is high for the duty cycle; clear, it starts low and rises, so it is low for it
+const FALLING_EDGE: u16 = 0x8000;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Polarity {
+    // the LED lights up when the pin is low, e.g. a common anode RGB LED
+    ActiveLow,
+    // the LED lights up when the pin is high, e.g. a common cathode RGB LED
+    ActiveHigh,
+}
+
+impl Polarity {
+    fn off_level(&self) -> Level {
+        match self {
+            Polarity::ActiveLow => Level::High,
+            Polarity::ActiveHigh => Level::Low,
+        }
+    }
+
+    fn duty_flag(&self) -> u16 {
+        match self {
+            Polarity::ActiveLow => 0,
+            Polarity::ActiveHigh => FALLING_EDGE,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct ChannelPolarity {
+    pub red: Polarity,
+    pub green: Polarity,
+    pub blue: Polarity,
+}
+
+impl ChannelPolarity {
+    pub const COMMON_ANODE: ChannelPolarity = ChannelPolarity::all(Polarity::ActiveLow);
+    pub const COMMON_CATHODE: ChannelPolarity = ChannelPolarity::all(Polarity::ActiveHigh);
+
+    pub const fn all(polarity: Polarity) -> ChannelPolarity {
+        ChannelPolarity {
+            red: polarity,
+            green: polarity,
+            blue: polarity,
+        }
+    }
+}
+
+// anything that can show the current air quality as a colour
+pub trait Indicator {
+    fn set_colour(&mut self, colour: Colour);
+    fn colour(&self) -> Colour;
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct Colour {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Colour {
+    pub const OFF: Colour = Colour::new(0, 0, 0);
+    pub const RED: Colour = Colour::new(255, 0, 0);
+    pub const GREEN: Colour = Colour::new(0, 255, 0);
+    pub const BLUE: Colour = Colour::new(0, 0, 255);
+    pub const YELLOW: Colour = Colour::new(255, 255, 0);
+    pub const WHITE: Colour = Colour::new(255, 255, 255);
+
+    pub const fn new(r: u8, g: u8, b: u8) -> Colour {
+        Colour { r, g, b }
+    }
+
+    // linear interpolation, `amount` from 0.0 (self) to 1.0 (other)
+    pub fn mix(&self, other: &Colour, amount: f32) -> Colour {
+        let amount = amount.clamp(0.0, 1.0);
+        let channel = |from: u8, to: u8| (from as f32 + (to as f32 - from as f32) * amount) as u8;
+
+        Colour::new(
+            channel(self.r, other.r),
+            channel(self.g, other.g),
+            channel(self.b, other.b),
+        )
+    }
+
+    pub fn scale(&self, factor: f32) -> Colour {
+        Colour::OFF.mix(self, factor)
+    }
+
+    // green up to the first warning level, yellow at the second and red from the limit on
+    pub fn from_co2(co2: f32, thresholds: &Thresholds) -> Colour {
+        if co2 <= thresholds.warning_level_1 {
+            Colour::GREEN
+        } else if co2 <= thresholds.warning_level_2 {
+            let amount = (co2 - thresholds.warning_level_1)
+                / (thresholds.warning_level_2 - thresholds.warning_level_1);
+            Colour::GREEN.mix(&Colour::YELLOW, amount)
+        } else if co2 < thresholds.limit_level {
+            let amount = (co2 - thresholds.warning_level_2)
+                / (thresholds.limit_level - thresholds.warning_level_2);
+            Colour::YELLOW.mix(&Colour::RED, amount)
+        } else {
+            Colour::RED
+        }
+    }
+}
+
 pub struct LEDColour {
-    r: Pin<Output<PushPull>>,
-    b: Pin<Output<PushPull>>,
-    g: Pin<Output<PushPull>>,
+    pwm: PWM1,
+    _pins: [Pin<Output<PushPull>>; 3],
+    // EasyDMA reads the duty cycles from RAM, one value per PWM channel
+    duty: &'static mut [u16; 4],
+    polarity: [Polarity; 3],
+    // the base colour, animations are drawn on top of it
+    colour: Colour,
+    shown: Colour,
+    brightness: u8,
+    animator: Animator,
 }
 
 impl LEDColour {
-    pub fn init<Mode>(led_red: Pin<Mode>, led_blue: Pin<Mode>, led_green: Pin<Mode>) -> LEDColour {
-        LEDColour {
-            r: led_red.into_push_pull_output(Level::High),
-            b: led_blue.into_push_pull_output(Level::High),
-            g: led_green.into_push_pull_output(Level::High),
+    pub fn init<Mode>(
+        pwm: PWM1,
+        led_red: Pin<Mode>,
+        led_blue: Pin<Mode>,
+        led_green: Pin<Mode>,
+    ) -> LEDColour {
+        LEDColour::init_with_polarity(
+            pwm,
+            led_red,
+            led_blue,
+            led_green,
+            ChannelPolarity::COMMON_ANODE,
+        )
+    }
+
+    pub fn init_with_polarity<Mode>(
+        pwm: PWM1,
+        led_red: Pin<Mode>,
+        led_blue: Pin<Mode>,
+        led_green: Pin<Mode>,
+        polarity: ChannelPolarity,
+    ) -> LEDColour {
+        let polarity = [polarity.red, polarity.green, polarity.blue];
+        let pins = [
+            led_red.into_push_pull_output(polarity[0].off_level()),
+            led_green.into_push_pull_output(polarity[1].off_level()),
+            led_blue.into_push_pull_output(polarity[2].off_level()),
+        ];
+
+        for (channel, pin) in pins.iter().enumerate() {
+            pwm.psel.out[channel].write(|w| {
+                unsafe { w.pin().bits(pin.pin()) };
+                w.port().bit(matches!(pin.port(), Port::Port1));
+                w.connect().connected()
+            });
+        }
+
+        pwm.enable.write(|w| w.enable().enabled());
+        pwm.mode.write(|w| w.updown().up());
+        pwm.prescaler.write(|w| w.prescaler().div_16());
+        pwm.countertop
+            .write(|w| unsafe { w.countertop().bits(COUNTERTOP) });
+        pwm.decoder
+            .write(|w| w.load().individual().mode().refresh_count());
+        pwm.loop_.write(|w| w.cnt().disabled());
+
+        let duty = cortex_m::singleton!(: [u16; 4] = [0; 4]).unwrap();
+
+        pwm.seq0
+            .ptr
+            .write(|w| unsafe { w.bits(duty.as_ptr() as u32) });
+        pwm.seq0.cnt.write(|w| unsafe { w.bits(4) });
+        pwm.seq0.refresh.write(|w| unsafe { w.bits(0) });
+        pwm.seq0.enddelay.write(|w| unsafe { w.bits(0) });
+
+        let mut led = LEDColour {
+            pwm,
+            _pins: pins,
+            duty,
+            polarity,
+            colour: Colour::OFF,
+            shown: Colour::OFF,
+            brightness: 255,
+            animator: Animator::new(),
+        };
+        led.apply(Colour::OFF);
+
+        led
+    }
+
+    pub fn colour(&self) -> Colour {
+        self.colour
+    }
+
+    // the colour currently lit, which differs from `colour` while an animation runs
+    pub fn shown(&self) -> Colour {
+        self.shown
+    }
+
+    pub fn set_rgb(&mut self, r: u8, g: u8, b: u8) {
+        self.set_colour(Colour::new(r, g, b));
+    }
+
+    pub fn set_colour(&mut self, colour: Colour) {
+        self.colour = colour;
+        if !self.animator.is_animating() {
+            self.apply(colour);
         }
     }
 
+    // scales all channels, 255 is full brightness
+    pub fn set_brightness(&mut self, brightness: u8) {
+        self.brightness = brightness;
+        self.apply(self.shown);
+    }
+
+    pub fn animate(&mut self, animation: Animation, millis: u64) {
+        self.animator.play(animation, millis);
+        self.update(millis);
+    }
+
+    pub fn set_background_animation(&mut self, animation: Option<Animation>, millis: u64) {
+        self.animator.set_background(animation, millis);
+        self.update(millis);
+    }
+
+    pub fn stop_animation(&mut self) {
+        self.animator.stop();
+        self.apply(self.colour);
+    }
+
+    pub fn is_animating(&self) -> bool {
+        self.animator.is_animating()
+    }
+
+    // advances the animations, call this from the main loop tick
+    pub fn update(&mut self, millis: u64) {
+        let frame = self.animator.frame(millis, self.colour);
+        if frame != self.shown {
+            self.apply(frame);
+        }
+    }
+
+    pub fn show_co2(&mut self, co2: f32, thresholds: &Thresholds) {
+        self.set_colour(Colour::from_co2(co2, thresholds));
+    }
+
+    fn apply(&mut self, colour: Colour) {
+        self.shown = colour;
+        let brightness = self.brightness as f32 / 255.0;
+
+        let values = [colour.r, colour.g, colour.b];
+
+        for ((duty, value), polarity) in self
+            .duty
+            .iter_mut()
+            .zip(values.iter())
+            .zip(self.polarity.iter())
+        {
+            *duty = gamma_correct(*value as f32 / 255.0 * brightness) | polarity.duty_flag();
+        }
+
+        self.pwm.tasks_seqstart[0].write(|w| unsafe { w.bits(1) });
+    }
+
     pub fn off(&mut self) {
-        self.r.set_high().unwrap();
-        self.b.set_high().unwrap();
-        self.g.set_high().unwrap();
+        self.set_colour(Colour::OFF);
     }
 
     pub fn red(&mut self) {
-        self.r.set_low().unwrap();
-        self.g.set_high().unwrap();
-        self.b.set_high().unwrap();
+        self.set_colour(Colour::RED);
     }
 
     pub fn blue(&mut self) {
-        self.r.set_high().unwrap();
-        self.g.set_high().unwrap();
-        self.b.set_low().unwrap();
+        self.set_colour(Colour::BLUE);
     }
 
     pub fn green(&mut self) {
-        self.r.set_high().unwrap();
-        self.g.set_low().unwrap();
-        self.b.set_high().unwrap();
+        self.set_colour(Colour::GREEN);
     }
 
     pub fn yellow(&mut self) {
-        self.r.set_low().unwrap();
-        self.b.set_high().unwrap();
-        self.g.set_low().unwrap();
+        self.set_colour(Colour::YELLOW);
     }
 
     pub fn white(&mut self) {
-        self.r.set_low().unwrap();
-        self.g.set_low().unwrap();
-        self.b.set_low().unwrap();
+        self.set_colour(Colour::WHITE);
     }
 
-    pub fn blink(&mut self, timer: &mut Timer<TIMER1, OneShot>) {
-        let current_red = self.r.is_set_high().unwrap();
-        let current_green = self.g.is_set_high().unwrap();
-        let current_blue = self.b.is_set_high().unwrap();
-
-        self.white();
-        timer.delay_ms(100_u32);
-
-        let result = if current_red {
-            self.r.set_high()
-        } else {
-            self.r.set_low()
-        };
-        result.unwrap();
+    pub fn blink(&mut self, millis: u64) {
+        self.animate(Animation::blink(1), millis);
+    }
+}
 
-        let result = if current_green {
-            self.g.set_high()
-        } else {
-            self.g.set_low()
-        };
-        result.unwrap();
+impl Indicator for LEDColour {
+    fn set_colour(&mut self, colour: Colour) {
+        LEDColour::set_colour(self, colour);
+    }
 
-        let result = if current_blue {
-            self.b.set_high()
-        } else {
-            self.b.set_low()
-        };
-        result.unwrap();
+    fn colour(&self) -> Colour {
+        LEDColour::colour(self)
     }
 }
+
+// perceived brightness to duty cycle
+fn gamma_correct(level: f32) -> u16 {
+    (libm::powf(level.clamp(0.0, 1.0), GAMMA) * COUNTERTOP as f32) as u16
+}
@@ -15,8 +15,8 @@ enum Command {
     MeasurementInterval = 0x4600,
     GetDataReadyStatus = 0x0202,
     ReadMeasurement = 0x0300,
-    ASC = 0x5306,
-    // FRC = 0x5204,
+    Asc = 0x5306,
+    Frc = 0x5204,
     TemperatureOffset = 0x5403,
     // AltitudeCompensation = 0x5102,
     ReadFirmwareVersion = 0xd100,
@@ -146,6 +146,28 @@ where
         Ok(())
     }
 
+    // the sensor must have run in continuous mode for at least 2 minutes at a known concentration
+    pub fn set_forced_recalibration(&mut self, reference_ppm: u16) -> Result<(), E> {
+        let argument_bytes = &reference_ppm.to_be_bytes();
+
+        let mut crc = self.get_crc();
+        crc.update(argument_bytes);
+
+        let command = (Command::Frc as u16).to_be_bytes();
+
+        let command: [u8; 5] = [
+            command[0],
+            command[1],
+            argument_bytes[0],
+            argument_bytes[1],
+            crc.finish(),
+        ];
+
+        self.0.write(DEFAULT_ADDRESS, &command)?;
+
+        Ok(())
+    }
+
     pub fn get_measurement_interval(&mut self) -> Result<u16, E> {
         let mut rd_buffer = [0u8; 3];
 
@@ -203,7 +225,7 @@ where
         let mut crc = self.get_crc();
         crc.update(&argument_bytes);
 
-        let command = (Command::ASC as u16).to_be_bytes();
+        let command = (Command::Asc as u16).to_be_bytes();
 
         let command: [u8; 5] = [
             command[0],
@@ -216,7 +238,7 @@ where
         self.0.write(DEFAULT_ADDRESS, &command)?;
 
         self.0
-            .write(DEFAULT_ADDRESS, &(Command::ASC as u16).to_be_bytes())?;
+            .write(DEFAULT_ADDRESS, &(Command::Asc as u16).to_be_bytes())?;
 
         let mut rd_buffer = [0u8; 3];
 
@@ -0,0 +1,446 @@
+use core::fmt::{self, Write};
+
+use super::Settings;
+use crate::{
+    alert::profile::Profile,
+    clock::{DateTime, TimeOfDay, Weekday, Weekdays},
+    display_helper::screen::{Layout, LAYOUTS},
+    number_representations::Unit,
+};
+
+// the quiet days to choose from
+const QUIET_DAYS: [Weekdays; 3] = [Weekdays::EVERY_DAY, Weekdays::WORKDAYS, Weekdays::WEEKEND];
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Item {
+    WarningLevel1,
+    WarningLevel2,
+    LimitLevel,
+    MeasurementInterval,
+    TemperatureOffset,
+    Pressure,
+    Layout,
+    QuietStart,
+    QuietEnd,
+    QuietDays,
+    Beeps,
+    RepeatInterval,
+    Repetitions,
+    RealertAfter,
+    // the wall clock, set when the menu closes but not stored
+    Day,
+    Hour,
+    Minute,
+}
+
+pub const ITEMS: [Item; 17] = [
+    Item::WarningLevel1,
+    Item::WarningLevel2,
+    Item::LimitLevel,
+    Item::MeasurementInterval,
+    Item::TemperatureOffset,
+    Item::Pressure,
+    Item::Layout,
+    Item::QuietStart,
+    Item::QuietEnd,
+    Item::QuietDays,
+    Item::Beeps,
+    Item::RepeatInterval,
+    Item::Repetitions,
+    Item::RealertAfter,
+    Item::Day,
+    Item::Hour,
+    Item::Minute,
+];
+
+impl Item {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Item::WarningLevel1 => "Warning 1",
+            Item::WarningLevel2 => "Warning 2",
+            Item::LimitLevel => "Limit",
+            Item::MeasurementInterval => "Interval",
+            Item::TemperatureOffset => "Temp offset",
+            Item::Pressure => "Pressure",
+            Item::Layout => "Main view",
+            Item::QuietStart => "Quiet from",
+            Item::QuietEnd => "Quiet to",
+            Item::QuietDays => "Quiet days",
+            Item::Beeps => "Beeps",
+            Item::RepeatInterval => "Repeat every",
+            Item::Repetitions => "Repeats",
+            Item::RealertAfter => "Re-alert",
+            Item::Day => "Day",
+            Item::Hour => "Hour",
+            Item::Minute => "Minute",
+        }
+    }
+
+    fn step(&self) -> u16 {
+        match self {
+            Item::WarningLevel1 | Item::WarningLevel2 | Item::LimitLevel => 50,
+            Item::MeasurementInterval => 1,
+            Item::TemperatureOffset => 10,
+            Item::Pressure | Item::Layout => 1,
+            Item::QuietStart | Item::QuietEnd => 15,
+            Item::Beeps | Item::Repetitions => 1,
+            Item::RepeatInterval | Item::RealertAfter => 5,
+            Item::QuietDays | Item::Day | Item::Hour | Item::Minute => 1,
+        }
+    }
+
+    // the thresholds keep their order
+    pub fn range(&self, settings: &Settings) -> (u16, u16) {
+        let (warning_level_1, warning_level_2, limit_level) = levels(settings);
+
+        match self {
+            Item::WarningLevel1 => (400, warning_level_2.saturating_sub(self.step())),
+            Item::WarningLevel2 => (
+                warning_level_1.saturating_add(self.step()),
+                limit_level.saturating_sub(self.step()),
+            ),
+            Item::LimitLevel => (warning_level_2.saturating_add(self.step()), 5000),
+            // the limits of the SCD30
+            Item::MeasurementInterval => (2, 1800),
+            Item::TemperatureOffset => (0, 1000),
+            Item::Pressure => (700, 1400),
+            Item::Layout => (0, LAYOUTS.len() as u16 - 1),
+            // minutes of the day
+            Item::QuietStart | Item::QuietEnd => (0, 24 * 60 - self.step()),
+            Item::QuietDays => (0, QUIET_DAYS.len() as u16 - 1),
+            Item::Beeps => (1, 10),
+            // seconds
+            Item::RepeatInterval => (5, 600),
+            Item::Repetitions => (0, 20),
+            // minutes, 0 turns it off
+            Item::RealertAfter => (0, 240),
+            Item::Day => (0, 6),
+            Item::Hour => (0, 23),
+            Item::Minute => (0, 59),
+        }
+    }
+
+    pub fn value(&self, settings: &Settings, clock: &DateTime) -> u16 {
+        let (warning_level_1, warning_level_2, limit_level) = levels(settings);
+
+        match self {
+            Item::WarningLevel1 => warning_level_1,
+            Item::WarningLevel2 => warning_level_2,
+            Item::LimitLevel => limit_level,
+            Item::MeasurementInterval => settings.measurement_interval,
+            Item::TemperatureOffset => settings.temperature_offset,
+            Item::Pressure => settings.pressure,
+            Item::Layout => settings.layout.index(),
+            Item::QuietStart => settings.quiet_start.minutes(),
+            Item::QuietEnd => settings.quiet_end.minutes(),
+            Item::QuietDays => QUIET_DAYS
+                .iter()
+                .position(|days| *days == settings.quiet_days)
+                .unwrap_or(0) as u16,
+            Item::Beeps => settings.escalation.initial_beeps,
+            Item::RepeatInterval => (settings.escalation.repeat_interval_ms / 1000) as u16,
+            Item::Repetitions => settings.escalation.max_repetitions,
+            Item::RealertAfter => settings
+                .escalation
+                .realert_after_ms
+                .map_or(0, |ms| (ms / 60_000) as u16),
+            Item::Day => clock.weekday.index() as u16,
+            Item::Hour => clock.time.hour as u16,
+            Item::Minute => clock.time.minute as u16,
+        }
+    }
+
+    fn set(&self, settings: &mut Settings, clock: &mut DateTime, value: u16) {
+        match self {
+            Item::WarningLevel1 | Item::WarningLevel2 | Item::LimitLevel => {
+                let mut thresholds = settings.thresholds();
+                let level = match self {
+                    Item::WarningLevel1 => &mut thresholds.warning_level_1,
+                    Item::WarningLevel2 => &mut thresholds.warning_level_2,
+                    _ => &mut thresholds.limit_level,
+                };
+
+                // a preset only turns into a custom profile when a threshold changes
+                if *level != value as f32 {
+                    *level = value as f32;
+                    settings.profile = Profile::Custom(thresholds);
+                }
+            }
+            Item::MeasurementInterval => settings.measurement_interval = value,
+            Item::TemperatureOffset => settings.temperature_offset = value,
+            Item::Pressure => settings.pressure = value,
+            Item::Layout => settings.layout = Layout::from_index(value),
+            Item::QuietStart => settings.quiet_start = TimeOfDay::from_minutes(value),
+            Item::QuietEnd => settings.quiet_end = TimeOfDay::from_minutes(value),
+            Item::QuietDays => settings.quiet_days = QUIET_DAYS[value as usize],
+            Item::Beeps => settings.escalation.initial_beeps = value,
+            Item::RepeatInterval => settings.escalation.repeat_interval_ms = value as u64 * 1000,
+            Item::Repetitions => settings.escalation.max_repetitions = value,
+            Item::RealertAfter => {
+                settings.escalation.realert_after_ms =
+                    Some(value as u64 * 60_000).filter(|ms| *ms > 0)
+            }
+            Item::Day => clock.weekday = Weekday::from_index(value as u32),
+            Item::Hour => clock.time = TimeOfDay::new(value as u8, clock.time.minute),
+            Item::Minute => clock.time = TimeOfDay::new(clock.time.hour, value as u8),
+        }
+    }
+
+    // the offset is kept in 0.01 °C and only shown in the selected unit
+    pub fn write_value<W: Write>(&self, value: u16, unit: Unit, out: &mut W) -> fmt::Result {
+        match self {
+            Item::WarningLevel1 | Item::WarningLevel2 | Item::LimitLevel => {
+                write!(out, "{} ppm", value)
+            }
+            Item::MeasurementInterval => write!(out, "{} s", value),
+            Item::TemperatureOffset => {
+                let offset = unit.convert_difference(&(value as f32 / 100.0));
+                write!(out, "{:.2} {}", offset, unit.symbol())
+            }
+            Item::Pressure => write!(out, "{} mbar", value),
+            Item::Layout => write!(out, "{}", Layout::from_index(value).name()),
+            Item::QuietStart | Item::QuietEnd => {
+                let time = TimeOfDay::from_minutes(value);
+                write!(out, "{:02}:{:02}", time.hour, time.minute)
+            }
+            Item::QuietDays => match value {
+                0 => write!(out, "Every day"),
+                1 => write!(out, "Mon - Fri"),
+                _ => write!(out, "Sat, Sun"),
+            },
+            Item::Beeps | Item::Repetitions => write!(out, "{}", value),
+            Item::RepeatInterval => write!(out, "{} s", value),
+            Item::RealertAfter if value == 0 => write!(out, "off"),
+            Item::RealertAfter => write!(out, "{} min", value),
+            Item::Day => write!(out, "{}", Weekday::from_index(value as u32).name()),
+            Item::Hour | Item::Minute => write!(out, "{:02}", value),
+        }
+    }
+}
+
+// the thresholds of the profile in whole ppm
+fn levels(settings: &Settings) -> (u16, u16, u16) {
+    let thresholds = settings.thresholds();
+    (
+        libm::roundf(thresholds.warning_level_1) as u16,
+        libm::roundf(thresholds.warning_level_2) as u16,
+        libm::roundf(thresholds.limit_level) as u16,
+    )
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum MenuInput {
+    Up,
+    Down,
+    Select,
+    Back,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum MenuResult {
+    Open,
+    // the edited settings and the clock, None if they didn't change
+    Closed(Option<Settings>, Option<DateTime>),
+}
+
+// select starts editing the highlighted item and select again takes the new value, back
+// drops the edit or leaves the menu
+pub struct Menu {
+    original: Settings,
+    settings: Settings,
+    original_clock: DateTime,
+    clock: DateTime,
+    selected: usize,
+    editing: Option<u16>,
+}
+
+impl Menu {
+    // `now` from the wall clock, the seconds start over when the clock is set
+    pub fn open(settings: Settings, now: DateTime) -> Menu {
+        let clock = DateTime { second: 0, ..now };
+
+        Menu {
+            original: settings,
+            settings,
+            original_clock: clock,
+            clock,
+            selected: 0,
+            editing: None,
+        }
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    pub fn is_editing(&self) -> bool {
+        self.editing.is_some()
+    }
+
+    // the value to show, the one being edited for the selected item
+    pub fn value(&self, index: usize) -> u16 {
+        match self.editing {
+            Some(value) if index == self.selected => value,
+            _ => ITEMS[index].value(&self.settings, &self.clock),
+        }
+    }
+
+    pub fn handle(&mut self, input: MenuInput) -> MenuResult {
+        let item = ITEMS[self.selected];
+
+        match (self.editing, input) {
+            (None, MenuInput::Up) => {
+                self.selected = (self.selected + ITEMS.len() - 1) % ITEMS.len()
+            }
+            (None, MenuInput::Down) => self.selected = (self.selected + 1) % ITEMS.len(),
+            (None, MenuInput::Select) => {
+                self.editing = Some(item.value(&self.settings, &self.clock))
+            }
+            (None, MenuInput::Back) => {
+                let settings = Some(self.settings).filter(|settings| *settings != self.original);
+                let clock = Some(self.clock).filter(|clock| *clock != self.original_clock);
+                return MenuResult::Closed(settings, clock);
+            }
+            (Some(value), MenuInput::Up) => {
+                let (_, max) = item.range(&self.settings);
+                self.editing = Some(value.saturating_add(item.step()).min(max));
+            }
+            (Some(value), MenuInput::Down) => {
+                let (min, _) = item.range(&self.settings);
+                self.editing = Some(value.saturating_sub(item.step()).max(min));
+            }
+            (Some(value), MenuInput::Select) => {
+                item.set(&mut self.settings, &mut self.clock, value);
+                self.editing = None;
+            }
+            (Some(_), MenuInput::Back) => self.editing = None,
+        }
+
+        MenuResult::Open
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrayvec::ArrayString;
+
+    use crate::alert::profile::Thresholds;
+
+    fn open_menu() -> Menu {
+        let now = DateTime {
+            weekday: Weekday::Tuesday,
+            time: TimeOfDay::new(8, 30),
+            second: 12,
+        };
+
+        Menu::open(Settings::default(), now)
+    }
+
+    fn select(menu: &mut Menu, item: Item) {
+        while ITEMS[menu.selected()] != item {
+            menu.handle(MenuInput::Down);
+        }
+    }
+
+    #[test]
+    fn setting_the_clock_leaves_the_settings_alone() {
+        let mut menu = open_menu();
+        select(&mut menu, Item::Hour);
+        menu.handle(MenuInput::Select);
+        menu.handle(MenuInput::Up);
+        menu.handle(MenuInput::Select);
+
+        let clock = DateTime {
+            weekday: Weekday::Tuesday,
+            time: TimeOfDay::new(9, 30),
+            second: 0,
+        };
+        assert!(menu.handle(MenuInput::Back) == MenuResult::Closed(None, Some(clock)));
+    }
+
+    #[test]
+    fn quiet_hours_move_in_quarter_hours() {
+        let mut menu = open_menu();
+        select(&mut menu, Item::QuietEnd);
+        menu.handle(MenuInput::Select);
+        menu.handle(MenuInput::Down);
+        menu.handle(MenuInput::Select);
+
+        let settings = Settings {
+            quiet_end: TimeOfDay::new(6, 45),
+            ..Settings::default()
+        };
+        assert!(menu.handle(MenuInput::Back) == MenuResult::Closed(Some(settings), None));
+    }
+
+    #[test]
+    fn quiet_days_cycle_through_the_presets() {
+        let mut menu = open_menu();
+        select(&mut menu, Item::QuietDays);
+        menu.handle(MenuInput::Select);
+        menu.handle(MenuInput::Up);
+        menu.handle(MenuInput::Up);
+        menu.handle(MenuInput::Up);
+        menu.handle(MenuInput::Select);
+
+        let settings = Settings {
+            quiet_days: Weekdays::WEEKEND,
+            ..Settings::default()
+        };
+        assert!(menu.handle(MenuInput::Back) == MenuResult::Closed(Some(settings), None));
+    }
+
+    #[test]
+    fn realert_can_be_turned_off() {
+        let mut menu = open_menu();
+        select(&mut menu, Item::RealertAfter);
+        menu.handle(MenuInput::Select);
+        for _ in 0..10 {
+            menu.handle(MenuInput::Down);
+        }
+        menu.handle(MenuInput::Select);
+
+        match menu.handle(MenuInput::Back) {
+            MenuResult::Closed(Some(settings), None) => {
+                assert!(settings.escalation.realert_after_ms.is_none())
+            }
+            _ => panic!("the settings didn't change"),
+        }
+    }
+
+    #[test]
+    fn temperature_offset_is_shown_in_the_unit() {
+        let mut text = ArrayString::<[_; 16]>::new();
+        Item::TemperatureOffset
+            .write_value(150, Unit::Fahrenheit, &mut text)
+            .unwrap();
+        assert_eq!(text.as_str(), "2.70 °F");
+
+        text.clear();
+        Item::TemperatureOffset
+            .write_value(150, Unit::Kelvin, &mut text)
+            .unwrap();
+        assert_eq!(text.as_str(), "1.50 K");
+    }
+
+    #[test]
+    fn unchanged_thresholds_keep_the_preset() {
+        let mut menu = open_menu();
+        menu.handle(MenuInput::Select);
+        menu.handle(MenuInput::Select);
+        assert!(menu.handle(MenuInput::Back) == MenuResult::Closed(None, None));
+
+        let mut menu = open_menu();
+        menu.handle(MenuInput::Select);
+        menu.handle(MenuInput::Up);
+        menu.handle(MenuInput::Select);
+
+        match menu.handle(MenuInput::Back) {
+            MenuResult::Closed(Some(settings), None) => {
+                assert!(settings.profile == Profile::Custom(Thresholds::new(550.0, 700.0, 1000.0)))
+            }
+            _ => panic!("the settings didn't change"),
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use crate::{
+    alert::{
+        escalation::EscalationPolicy,
+        profile::{Profile, Thresholds},
+        quiet_hours::QuietHours,
+    },
+    clock::{TimeOfDay, Weekdays},
+    display_helper::screen::Layout,
+};
+
+pub mod menu;
+pub mod storage;
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct Settings {
+    // a preset, or custom thresholds once one was edited in the menu
+    pub profile: Profile,
+    // seconds between two measurements
+    pub measurement_interval: u16,
+    // in 0.01 °C, subtracted from the measured temperature by the SCD30
+    pub temperature_offset: u16,
+    // ambient pressure in mbar for the CO2 compensation
+    pub pressure: u16,
+    pub layout: Layout,
+    // the buzzer stays silent in between; the same time for both turns it off
+    pub quiet_start: TimeOfDay,
+    pub quiet_end: TimeOfDay,
+    // the days on which the quiet hours start
+    pub quiet_days: Weekdays,
+    // the intervals are stored in whole seconds and minutes
+    pub escalation: EscalationPolicy,
+}
+
+impl Settings {
+    pub fn thresholds(&self) -> Thresholds {
+        self.profile.thresholds()
+    }
+
+    pub fn quiet_hours(&self) -> Option<QuietHours> {
+        if self.quiet_start == self.quiet_end {
+            return None;
+        }
+
+        Some(QuietHours::new(
+            self.quiet_start,
+            self.quiet_end,
+            self.quiet_days,
+        ))
+    }
+
+    // the thresholds are stored for presets too, but only read back for a custom profile
+    fn to_words(self) -> [u32; 8] {
+        let thresholds = self.thresholds();
+
+        [
+            (thresholds.warning_level_1 as u32) << 16 | thresholds.warning_level_2 as u32,
+            (thresholds.limit_level as u32) << 16 | self.measurement_interval as u32,
+            (self.temperature_offset as u32) << 16 | self.pressure as u32,
+            (self.profile.index() as u32) << 16 | self.layout.index() as u32,
+            (self.quiet_start.minutes() as u32) << 16 | self.quiet_end.minutes() as u32,
+            self.quiet_days.bits() as u32,
+            (self.escalation.initial_beeps as u32) << 16 | self.escalation.max_repetitions as u32,
+            ((self.escalation.repeat_interval_ms / 1000) as u32) << 16
+                | self.escalation.realert_after_ms.map_or(0, |ms| ms / 60_000) as u32,
+        ]
+    }
+
+    fn from_words(words: &[u32; 8]) -> Settings {
+        let thresholds = Thresholds::new(
+            (words[0] >> 16) as u16 as f32,
+            words[0] as u16 as f32,
+            (words[1] >> 16) as u16 as f32,
+        );
+
+        Settings {
+            profile: Profile::preset((words[3] >> 16) as u16)
+                .unwrap_or(Profile::Custom(thresholds)),
+            measurement_interval: words[1] as u16,
+            temperature_offset: (words[2] >> 16) as u16,
+            pressure: words[2] as u16,
+            layout: Layout::from_index(words[3] as u16),
+            quiet_start: TimeOfDay::from_minutes((words[4] >> 16) as u16),
+            quiet_end: TimeOfDay::from_minutes(words[4] as u16),
+            quiet_days: Weekdays::from_bits(words[5] as u8),
+            escalation: EscalationPolicy::new(
+                (words[6] >> 16) as u16,
+                (words[7] >> 16) as u64 * 1000,
+                words[6] as u16,
+                // 0 turns the re-alert off
+                Some(words[7] as u16 as u64 * 60_000).filter(|ms| *ms > 0),
+            ),
+        }
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            profile: Profile::Standard,
+            measurement_interval: 2,
+            temperature_offset: 0,
+            pressure: 1012,
+            layout: Layout::default(),
+            quiet_start: TimeOfDay::new(22, 0),
+            quiet_end: TimeOfDay::new(7, 0),
+            quiet_days: Weekdays::EVERY_DAY,
+            escalation: EscalationPolicy::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn presets_and_custom_profiles_are_restored() {
+        let settings = Settings {
+            profile: Profile::Classroom,
+            layout: Layout::BigDigits,
+            ..Settings::default()
+        };
+        assert!(Settings::from_words(&settings.to_words()) == settings);
+
+        let settings = Settings {
+            profile: Profile::Custom(Thresholds::new(600.0, 900.0, 1500.0)),
+            pressure: 950,
+            quiet_days: Weekdays::WEEKEND,
+            escalation: EscalationPolicy::new(3, 60 * 1000, 10, None),
+            ..Settings::default()
+        };
+        assert!(Settings::from_words(&settings.to_words()) == settings);
+    }
+}
@@ -0,0 +1,76 @@
+use core::ptr;
+
+use nrf52840_hal::pac::NVMC;
+
+use super::Settings;
+
+// the last 4 kB page of the 1 MB flash, kept out of the firmware by `memory.x`
+const PAGE_ADDRESS: u32 = 0x000F_F000;
+// bumped whenever the layout of the words changes, older settings are ignored
+const MAGIC: u32 = 0x5345_5436;
+// magic, the words of the settings and a checksum
+const SETTINGS_WORDS: usize = 8;
+const WORDS: usize = SETTINGS_WORDS + 2;
+
+fn checksum(words: &[u32]) -> u32 {
+    words
+        .iter()
+        .fold(MAGIC, |sum, word| sum.rotate_left(5) ^ word)
+}
+
+pub struct FlashStorage {
+    nvmc: NVMC,
+}
+
+impl FlashStorage {
+    pub fn init(nvmc: NVMC) -> FlashStorage {
+        FlashStorage { nvmc }
+    }
+
+    // None if nothing was saved yet or the page doesn't hold valid settings
+    pub fn load(&self) -> Option<Settings> {
+        let mut words = [0u32; WORDS];
+        for (index, word) in words.iter_mut().enumerate() {
+            *word = unsafe { ptr::read_volatile((PAGE_ADDRESS as *const u32).add(index)) };
+        }
+
+        let mut data = [0u32; SETTINGS_WORDS];
+        data.copy_from_slice(&words[1..=SETTINGS_WORDS]);
+
+        if words[0] != MAGIC || words[WORDS - 1] != checksum(&data) {
+            return None;
+        }
+
+        Some(Settings::from_words(&data))
+    }
+
+    pub fn save(&mut self, settings: &Settings) {
+        // every save erases the page, which only survives about 10 000 erase cycles
+        if self.load() == Some(*settings) {
+            return;
+        }
+
+        let data = settings.to_words();
+        let mut words = [MAGIC; WORDS];
+        words[1..=SETTINGS_WORDS].copy_from_slice(&data);
+        words[WORDS - 1] = checksum(&data);
+
+        self.nvmc.config.write(|w| w.wen().een());
+        self.nvmc
+            .erasepage()
+            .write(|w| unsafe { w.erasepage().bits(PAGE_ADDRESS) });
+        self.wait_ready();
+
+        self.nvmc.config.write(|w| w.wen().wen());
+        for (index, word) in words.iter().enumerate() {
+            unsafe { ptr::write_volatile((PAGE_ADDRESS as *mut u32).add(index), *word) };
+            self.wait_ready();
+        }
+
+        self.nvmc.config.write(|w| w.wen().ren());
+    }
+
+    fn wait_ready(&self) {
+        while self.nvmc.ready.read().ready().is_busy() {}
+    }
+}
@@ -0,0 +1,149 @@
+use core::convert::Infallible;
+use embedded_graphics::{geometry::Size, pixelcolor::BinaryColor, prelude::*};
+use embedded_hal::blocking::i2c::Write;
+
+pub const WIDTH: u32 = 128;
+pub const HEIGHT: u32 = 64;
+// eight rows of pixels share a byte of the display memory
+pub const PAGES: u32 = HEIGHT / 8;
+
+const DEFAULT_ADDRESS: u8 = 0x3C;
+// the control byte in front of every transfer, tells commands and display data apart
+const COMMAND: u8 = 0x00;
+const DATA: u8 = 0x40;
+
+enum Command {
+    DisplayOff = 0xAE,
+    DisplayOn = 0xAF,
+    ClockDivide = 0xD5,
+    Multiplex = 0xA8,
+    DisplayOffset = 0xD3,
+    StartLine = 0x40,
+    ChargePump = 0x8D,
+    MemoryMode = 0x20,
+    SegmentRemap = 0xA1,
+    ComScanDescending = 0xC8,
+    ComPins = 0xDA,
+    Contrast = 0x81,
+    Precharge = 0xD9,
+    VcomDeselect = 0xDB,
+    FollowMemory = 0xA4,
+    NormalDisplay = 0xA6,
+    ColumnAddress = 0x21,
+    PageAddress = 0x22,
+}
+
+// a byte per column of each page, the lowest bit is the top row; a set bit is lit
+pub struct Display128x64 {
+    buffer: [u8; (WIDTH * PAGES) as usize],
+}
+
+impl Display128x64 {
+    pub fn buffer(&self) -> &[u8] {
+        &self.buffer
+    }
+}
+
+impl Default for Display128x64 {
+    fn default() -> Self {
+        Display128x64 {
+            buffer: [0; (WIDTH * PAGES) as usize],
+        }
+    }
+}
+
+impl DrawTarget<BinaryColor> for Display128x64 {
+    type Error = Infallible;
+
+    fn draw_pixel(&mut self, pixel: Pixel<BinaryColor>) -> Result<(), Self::Error> {
+        let Pixel(point, color) = pixel;
+        if point.x < 0 || point.y < 0 || point.x >= WIDTH as i32 || point.y >= HEIGHT as i32 {
+            return Ok(());
+        }
+
+        let index = (point.y as u32 / 8 * WIDTH + point.x as u32) as usize;
+        let bit = 1 << (point.y % 8);
+        match color {
+            BinaryColor::On => self.buffer[index] |= bit,
+            BinaryColor::Off => self.buffer[index] &= !bit,
+        }
+
+        Ok(())
+    }
+
+    fn size(&self) -> Size {
+        Size::new(WIDTH, HEIGHT)
+    }
+}
+
+// 128x64 OLED on I2C, powered by its internal charge pump
+pub struct SSD1306<T>(T);
+
+impl<T, E> SSD1306<T>
+where
+    T: Write<Error = E>,
+{
+    pub fn init(i2c: T) -> Self {
+        SSD1306(i2c)
+    }
+
+    fn command(&mut self, command: Command, arguments: &[u8]) -> Result<(), E> {
+        let mut bytes = [COMMAND, command as u8, 0, 0];
+        bytes[2..2 + arguments.len()].copy_from_slice(arguments);
+
+        self.0
+            .write(DEFAULT_ADDRESS, &bytes[..2 + arguments.len()])?;
+
+        Ok(())
+    }
+
+    // the display memory keeps its content, so the first update has to send everything
+    pub fn power_on(&mut self) -> Result<(), E> {
+        self.command(Command::DisplayOff, &[])?;
+        self.command(Command::ClockDivide, &[0x80])?;
+        self.command(Command::Multiplex, &[HEIGHT as u8 - 1])?;
+        self.command(Command::DisplayOffset, &[0])?;
+        self.command(Command::StartLine, &[])?;
+        self.command(Command::ChargePump, &[0x14])?;
+        // horizontal addressing, the column and then the page advance after every byte
+        self.command(Command::MemoryMode, &[0x00])?;
+        // column 0 and row 0 in the top left corner
+        self.command(Command::SegmentRemap, &[])?;
+        self.command(Command::ComScanDescending, &[])?;
+        self.command(Command::ComPins, &[0x12])?;
+        self.command(Command::Contrast, &[0xCF])?;
+        self.command(Command::Precharge, &[0xF1])?;
+        self.command(Command::VcomDeselect, &[0x40])?;
+        self.command(Command::FollowMemory, &[])?;
+        self.command(Command::NormalDisplay, &[])?;
+        self.command(Command::DisplayOn, &[])?;
+
+        Ok(())
+    }
+
+    pub fn sleep(&mut self) -> Result<(), E> {
+        self.command(Command::DisplayOff, &[])
+    }
+
+    // the panel shows the memory straight away, there is no separate refresh
+    pub fn update_pages(
+        &mut self,
+        display: &Display128x64,
+        first_page: u32,
+        last_page: u32,
+    ) -> Result<(), E> {
+        let last_page = last_page.min(PAGES - 1);
+
+        self.command(Command::ColumnAddress, &[0, WIDTH as u8 - 1])?;
+        self.command(Command::PageAddress, &[first_page as u8, last_page as u8])?;
+
+        let mut bytes = [DATA; WIDTH as usize + 1];
+        for page in first_page..=last_page {
+            let start = (page * WIDTH) as usize;
+            bytes[1..].copy_from_slice(&display.buffer()[start..start + WIDTH as usize]);
+            self.0.write(DEFAULT_ADDRESS, &bytes)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,254 @@
+use arrayvec::ArrayVec;
+
+use crate::{clock::Weekday, scd30::SensorData};
+
+const MINUTE_MS: u64 = 60 * 1000;
+// the trend compares the last minute with the one this many minutes before
+const TREND_MINUTES: usize = 10;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Trend {
+    Rising,
+    Steady,
+    Falling,
+}
+
+impl Trend {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Trend::Rising => "rising",
+            Trend::Steady => "steady",
+            Trend::Falling => "falling",
+        }
+    }
+
+    // the display draws an arrow for it
+    pub fn symbol(&self) -> char {
+        match self {
+            Trend::Rising => '^',
+            Trend::Steady => '>',
+            Trend::Falling => 'v',
+        }
+    }
+}
+
+// today's minimum, maximum and average of one quantity and where it is heading
+pub struct RollingStatistics {
+    // a change over the trend window this small still counts as steady
+    steady_band: f32,
+    day: Option<Weekday>,
+    min: f32,
+    max: f32,
+    average: f32,
+    count: u32,
+    // averages of the finished minutes, the oldest first; minutes without a value are NaN
+    minutes: ArrayVec<[f32; TREND_MINUTES + 1]>,
+    minute_start: Option<u64>,
+    minute_sum: f32,
+    minute_count: u16,
+}
+
+impl RollingStatistics {
+    pub fn init(steady_band: f32) -> RollingStatistics {
+        RollingStatistics {
+            steady_band,
+            day: None,
+            min: f32::NAN,
+            max: f32::NAN,
+            average: f32::NAN,
+            count: 0,
+            minutes: ArrayVec::new(),
+            minute_start: None,
+            minute_sum: 0.0,
+            minute_count: 0,
+        }
+    }
+
+    // NaN until the first value of the day
+    pub fn min(&self) -> f32 {
+        self.min
+    }
+
+    pub fn max(&self) -> f32 {
+        self.max
+    }
+
+    pub fn average(&self) -> f32 {
+        self.average
+    }
+
+    pub fn trend(&self) -> Trend {
+        if !self.minutes.is_full() {
+            return Trend::Steady;
+        }
+
+        let change = self.minutes[TREND_MINUTES] - self.minutes[0];
+        if change > self.steady_band {
+            Trend::Rising
+        } else if change < -self.steady_band {
+            Trend::Falling
+        } else {
+            // also if either minute has no value
+            Trend::Steady
+        }
+    }
+
+    pub fn update(&mut self, millis: u64, day: Weekday, value: f32) {
+        if !value.is_finite() {
+            return;
+        }
+
+        if self.day != Some(day) {
+            self.day = Some(day);
+            self.count = 0;
+        }
+
+        if self.count == 0 {
+            self.min = value;
+            self.max = value;
+            self.average = value;
+        }
+        self.count = self.count.saturating_add(1);
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        // a running mean, a sum over the whole day would lose the precision of an f32
+        self.average += (value - self.average) / self.count as f32;
+
+        self.update_minutes(millis, value);
+    }
+
+    fn update_minutes(&mut self, millis: u64, value: f32) {
+        let mut start = *self.minute_start.get_or_insert(millis);
+
+        if millis.saturating_sub(start) >= (TREND_MINUTES as u64 + 2) * MINUTE_MS {
+            // the whole trend window passed without a value
+            self.minutes.clear();
+            start = millis;
+            self.minute_sum = 0.0;
+            self.minute_count = 0;
+        }
+
+        while millis.saturating_sub(start) >= MINUTE_MS {
+            let average = if self.minute_count > 0 {
+                self.minute_sum / self.minute_count as f32
+            } else {
+                f32::NAN
+            };
+
+            if self.minutes.is_full() {
+                self.minutes.remove(0);
+            }
+            self.minutes.push(average);
+
+            start += MINUTE_MS;
+            self.minute_sum = 0.0;
+            self.minute_count = 0;
+        }
+
+        self.minute_start = Some(start);
+        self.minute_sum += value;
+        self.minute_count = self.minute_count.saturating_add(1);
+    }
+}
+
+pub struct ReadingStatistics {
+    pub co2: RollingStatistics,
+    // in °C, like the sensor reports it
+    pub temperature: RollingStatistics,
+    pub humidity: RollingStatistics,
+}
+
+impl ReadingStatistics {
+    pub fn init() -> ReadingStatistics {
+        ReadingStatistics {
+            co2: RollingStatistics::init(25.0),
+            temperature: RollingStatistics::init(0.2),
+            humidity: RollingStatistics::init(1.0),
+        }
+    }
+
+    pub fn update(&mut self, millis: u64, day: Weekday, data: &SensorData) {
+        self.co2.update(millis, day, data.co2);
+        self.temperature.update(millis, day, data.temperature);
+        self.humidity.update(millis, day, data.humidity);
+    }
+}
+
+impl Default for ReadingStatistics {
+    fn default() -> Self {
+        ReadingStatistics::init()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a value every 30 s during each minute of `minutes`
+    fn feed(statistics: &mut RollingStatistics, minutes: core::ops::Range<u64>, value: f32) {
+        for half_minute in minutes.start * 2..minutes.end * 2 {
+            statistics.update(half_minute * 30_000, Weekday::Monday, value);
+        }
+    }
+
+    #[test]
+    fn min_max_and_average_start_over_at_midnight() {
+        let mut statistics = RollingStatistics::init(25.0);
+        assert!(statistics.min().is_nan());
+
+        statistics.update(0, Weekday::Monday, 800.0);
+        statistics.update(2_000, Weekday::Monday, 400.0);
+        statistics.update(4_000, Weekday::Monday, f32::NAN);
+        statistics.update(6_000, Weekday::Monday, 600.0);
+        assert_eq!(statistics.min(), 400.0);
+        assert_eq!(statistics.max(), 800.0);
+        assert_eq!(statistics.average(), 600.0);
+
+        statistics.update(8_000, Weekday::Tuesday, 500.0);
+        assert_eq!(statistics.min(), 500.0);
+        assert_eq!(statistics.max(), 500.0);
+        assert_eq!(statistics.average(), 500.0);
+    }
+
+    #[test]
+    fn trend_waits_for_the_whole_window() {
+        let mut statistics = RollingStatistics::init(25.0);
+
+        feed(&mut statistics, 0..1, 400.0);
+        feed(&mut statistics, 1..11, 1000.0);
+        assert!(statistics.trend() == Trend::Steady);
+
+        feed(&mut statistics, 11..12, 1000.0);
+        assert!(statistics.trend() == Trend::Rising);
+    }
+
+    #[test]
+    fn changes_within_the_band_are_steady() {
+        let mut statistics = RollingStatistics::init(25.0);
+        feed(&mut statistics, 0..1, 500.0);
+        feed(&mut statistics, 1..10, 510.0);
+        feed(&mut statistics, 10..12, 525.0);
+        assert!(statistics.trend() == Trend::Steady);
+
+        let mut statistics = RollingStatistics::init(25.0);
+        feed(&mut statistics, 0..1, 500.0);
+        feed(&mut statistics, 1..12, 526.0);
+        assert!(statistics.trend() == Trend::Rising);
+
+        let mut statistics = RollingStatistics::init(25.0);
+        feed(&mut statistics, 0..1, 500.0);
+        feed(&mut statistics, 1..12, 474.0);
+        assert!(statistics.trend() == Trend::Falling);
+    }
+
+    #[test]
+    fn long_gap_clears_the_trend() {
+        let mut statistics = RollingStatistics::init(25.0);
+        feed(&mut statistics, 0..1, 400.0);
+        feed(&mut statistics, 1..12, 1000.0);
+        assert!(statistics.trend() == Trend::Rising);
+
+        feed(&mut statistics, 30..32, 1000.0);
+        assert!(statistics.trend() == Trend::Steady);
+    }
+}
@@ -0,0 +1,252 @@
+use arrayvec::ArrayVec;
+
+use crate::alert::profile::Thresholds;
+
+// typical CO2 level of outdoor air the room decays towards
+const OUTDOOR_CO2: f32 = 420.0;
+
+// one sample every 30 s gives a 10 minute window
+const SAMPLE_INTERVAL_MS: u64 = 30 * 1000;
+const MAX_SAMPLES: usize = 20;
+const MIN_SAMPLES: usize = 4;
+const MIN_DECAY_SAMPLES: usize = 3;
+
+// changes slower than this are treated as steady
+const MIN_SLOPE_PPM_PER_MINUTE: f32 = 2.0;
+
+const MS_PER_MINUTE: f32 = 60.0 * 1000.0;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Advice {
+    Steady,
+    // minutes of ventilation needed to get back to the green level
+    Ventilate(u16),
+    // minutes until the limit is reached at the current rate of rise
+    LimitIn(u16),
+}
+
+pub struct VentilationAdvisor {
+    samples: ArrayVec<[(u64, f32); MAX_SAMPLES]>,
+}
+
+impl VentilationAdvisor {
+    pub fn init() -> VentilationAdvisor {
+        VentilationAdvisor {
+            samples: ArrayVec::new(),
+        }
+    }
+
+    pub fn update(&mut self, millis: u64, co2: f32) {
+        if let Some((last_millis, _)) = self.samples.last() {
+            if millis.saturating_sub(*last_millis) < SAMPLE_INTERVAL_MS {
+                return;
+            }
+        }
+
+        if self.samples.is_full() {
+            self.samples.remove(0);
+        }
+        self.samples.push((millis, co2));
+    }
+
+    pub fn advice(&self, thresholds: &Thresholds) -> Advice {
+        if self.samples.len() < MIN_SAMPLES {
+            return Advice::Steady;
+        }
+
+        let current = match self.samples.last() {
+            Some((_, co2)) => *co2,
+            None => return Advice::Steady,
+        };
+
+        // the direction is taken from the last few samples so a window opened
+        // after a long rise is picked up straight away
+        let recent = &self.samples[self.samples.len() - MIN_SAMPLES..];
+        let slope = match fit(recent, |co2| co2) {
+            Some(slope) => slope,
+            None => return Advice::Steady,
+        };
+
+        if slope > MIN_SLOPE_PPM_PER_MINUTE && current < thresholds.limit_level {
+            let minutes = (thresholds.limit_level - current) / slope;
+            return Advice::LimitIn(to_minutes(minutes));
+        }
+
+        if slope < -MIN_SLOPE_PPM_PER_MINUTE && current > thresholds.warning_level_1 {
+            return self.time_to_fresh_air(current, thresholds.warning_level_1);
+        }
+
+        Advice::Steady
+    }
+
+    // fresh air exchange gives an exponential decay towards the outdoor level:
+    // C(t) = C_out + (C_0 - C_out) * e^(-k * t), so ln(C - C_out) is linear in t
+    fn time_to_fresh_air(&self, current: f32, target: f32) -> Advice {
+        // the fit starts at the last sample of the peak, when the window was opened, so the
+        // plateau CO2 often sits on beforehand is left out
+        let peak = self
+            .samples
+            .iter()
+            .enumerate()
+            .fold(0, |peak, (index, (_, co2))| {
+                if *co2 >= self.samples[peak].1 {
+                    index
+                } else {
+                    peak
+                }
+            });
+        let decay = &self.samples[peak..];
+
+        if decay.len() < MIN_DECAY_SAMPLES
+            || target <= OUTDOOR_CO2
+            || decay.iter().any(|(_, co2)| *co2 <= OUTDOOR_CO2)
+        {
+            return Advice::Steady;
+        }
+
+        let decay_rate = match fit(decay, |co2| libm::logf(co2 - OUTDOOR_CO2)) {
+            Some(slope) if slope < 0.0 => -slope,
+            _ => return Advice::Steady,
+        };
+
+        let minutes = libm::logf((current - OUTDOOR_CO2) / (target - OUTDOOR_CO2)) / decay_rate;
+
+        Advice::Ventilate(to_minutes(minutes))
+    }
+}
+
+impl Default for VentilationAdvisor {
+    fn default() -> Self {
+        VentilationAdvisor::init()
+    }
+}
+
+fn to_minutes(minutes: f32) -> u16 {
+    if minutes.is_finite() && minutes > 0.0 {
+        libm::ceilf(minutes).min(u16::MAX as f32) as u16
+    } else {
+        0
+    }
+}
+
+// least squares slope of f(co2) over time in minutes
+fn fit<F>(samples: &[(u64, f32)], f: F) -> Option<f32>
+where
+    F: Fn(f32) -> f32,
+{
+    let (start, _) = samples.first()?;
+    let n = samples.len() as f32;
+
+    let mut sum_t = 0.0;
+    let mut sum_y = 0.0;
+    let mut sum_tt = 0.0;
+    let mut sum_ty = 0.0;
+
+    for (millis, co2) in samples.iter() {
+        let t = (millis - start) as f32 / MS_PER_MINUTE;
+        let y = f(*co2);
+
+        sum_t += t;
+        sum_y += y;
+        sum_tt += t * t;
+        sum_ty += t * y;
+    }
+
+    let denominator = n * sum_tt - sum_t * sum_t;
+    if denominator <= f32::EPSILON {
+        return None;
+    }
+
+    Some((n * sum_ty - sum_t * sum_y) / denominator)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alert::profile::Profile;
+
+    const DECAY_RATE: f32 = 0.1;
+
+    // samples every 30 s, `co2` gets the minutes since the start
+    fn advisor<F: Fn(f32) -> f32>(samples: u64, co2: F) -> VentilationAdvisor {
+        let mut advisor = VentilationAdvisor::init();
+        for sample in 0..samples {
+            let millis = sample * SAMPLE_INTERVAL_MS;
+            advisor.update(millis, co2(millis as f32 / MS_PER_MINUTE));
+        }
+
+        advisor
+    }
+
+    fn decay(minutes: f32) -> f32 {
+        OUTDOOR_CO2 + (1500.0 - OUTDOOR_CO2) * libm::expf(-DECAY_RATE * minutes)
+    }
+
+    // the minutes until the decay reaches `target`, from `current`
+    fn expected(current: f32, target: f32) -> f32 {
+        libm::logf((current - OUTDOOR_CO2) / (target - OUTDOOR_CO2)) / DECAY_RATE
+    }
+
+    fn assert_ventilate(advice: Advice, minutes: f32, tolerance: f32) {
+        match advice {
+            Advice::Ventilate(actual) => assert!(
+                (actual as f32 - minutes).abs() <= tolerance,
+                "ventilate {} min instead of {}",
+                actual,
+                minutes
+            ),
+            _ => panic!("no ventilation advice"),
+        }
+    }
+
+    #[test]
+    fn decay_after_a_plateau() {
+        let thresholds = Profile::Standard.thresholds();
+        // flat for 5 minutes, then the window is opened
+        let advisor = advisor(20, |minutes| decay((minutes - 5.0).max(0.0)));
+
+        let current = decay(4.5);
+        assert_ventilate(
+            advisor.advice(&thresholds),
+            expected(current, thresholds.warning_level_1),
+            1.0,
+        );
+    }
+
+    #[test]
+    fn noisy_decay() {
+        let thresholds = Profile::Standard.thresholds();
+        let noise = |minutes: f32| {
+            if (minutes * 2.0) as u32 & 1 == 0 {
+                6.0
+            } else {
+                -6.0
+            }
+        };
+        let advisor = advisor(16, |minutes| decay(minutes) + noise(minutes));
+
+        let current = decay(7.5) + noise(7.5);
+        assert_ventilate(
+            advisor.advice(&thresholds),
+            expected(current, thresholds.warning_level_1),
+            2.0,
+        );
+    }
+
+    #[test]
+    fn rising_series() {
+        let thresholds = Profile::Standard.thresholds();
+        // 10 ppm per minute, 650 ppm after the last sample
+        let advisor = advisor(11, |minutes| 600.0 + 10.0 * minutes);
+
+        assert!(advisor.advice(&thresholds) == Advice::LimitIn(35));
+    }
+
+    #[test]
+    fn steady_without_enough_samples() {
+        let thresholds = Profile::Standard.thresholds();
+
+        assert!(advisor(3, decay).advice(&thresholds) == Advice::Steady);
+        assert!(advisor(20, |_| 800.0).advice(&thresholds) == Advice::Steady);
+    }
+}